@@ -0,0 +1,1060 @@
+use crate::commit_types::{self, CommitType};
+use crate::conventional::{Style, Translator};
+use crate::message::CommitMessage;
+use crate::rewrite;
+use crate::template::Value;
+use crate::workspace::Member;
+use chrono::{DateTime, FixedOffset};
+use clap::ValueEnum;
+use git2::{Commit, Oid, Repository, Revwalk, Sort};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR_STR};
+use std::sync::LazyLock;
+
+const SECTIONS: &str = include_str!("changelog.toml");
+
+/// The per-repository changelog sections, stored in the zazen directory.
+pub const CHANGELOG_FILE: &str = "changelog.toml";
+
+/// The section of the commits matching no other section.
+pub const OTHER_CHANGES: &str = "Other changes";
+
+/// The changelog kept at the root of the repository in the Keep a Changelog format.
+pub const CHANGELOG_MD: &str = "CHANGELOG.md";
+
+const UNRELEASED: &str = "Unreleased";
+const CHANGED: &str = "Changed";
+const KEEP_HEADINGS: [&str; 6] = [
+    "Added",
+    "Changed",
+    "Deprecated",
+    "Removed",
+    "Fixed",
+    "Security",
+];
+const KEEP_HEADER: &str = "# Changelog
+
+All notable changes to this project will be documented in this file.
+
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/),
+and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).
+";
+
+static VERSION_TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:(?P<package>.+)[-@])?v?(?P<version>\d+\.\d+\.\d+(?:[-+][0-9A-Za-z.+-]*)?)$")
+        .expect("invalid version tag regex")
+});
+
+///
+/// What the changelog action writes.
+///
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Format {
+    /// The release announcement, in zazen/logs
+    #[default]
+    Announcement,
+    /// The CHANGELOG.md of the repository, in the Keep a Changelog format
+    KeepAChangelog,
+    /// Every version with its commits grouped by section, in zazen/logs
+    Json,
+    /// Every version on a standalone page, in zazen/logs
+    Html,
+    /// An Atom feed with an entry per version, in zazen/logs
+    Atom,
+}
+
+///
+/// A section of the changelog and the commit types it lists.
+///
+#[derive(Deserialize, Clone, Debug)]
+pub struct Section {
+    pub title: String,
+    #[serde(default)]
+    pub types: Vec<String>,
+    #[serde(default)]
+    pub conventional: Vec<String>,
+    #[serde(default = "changed")]
+    pub keep: String,
+}
+
+fn changed() -> String {
+    String::from(CHANGED)
+}
+
+#[derive(Deserialize)]
+struct Sections {
+    #[serde(default = "keep_builtin")]
+    builtin: bool,
+    #[serde(default)]
+    remove: Vec<String>,
+    #[serde(default)]
+    sections: Vec<Section>,
+}
+
+const fn keep_builtin() -> bool {
+    true
+}
+
+fn builtin() -> Sections {
+    toml::from_str::<Sections>(SECTIONS).expect("the changelog sections are invalid")
+}
+
+fn read(r: &str) -> Option<Sections> {
+    let path: String = format!("{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}{CHANGELOG_FILE}");
+    if Path::new(path.as_str()).is_file().eq(&false) {
+        return None;
+    }
+    let sections: Sections = toml::from_str(
+        read_to_string(path.as_str())
+            .expect("failed to read the changelog sections")
+            .as_str(),
+    )
+    .unwrap_or_else(|e| panic!("{path} is not a valid changelog file : {e}"));
+    Some(sections)
+}
+
+///
+/// Load the changelog sections of the repository `r`, in their order.
+///
+/// The sections of `zazen/changelog.toml` are merged with the builtin ones, a section using the title of a builtin
+/// one replace it at its place and the others are added after them.
+///
+/// # Panics
+///
+/// If the sections file cannot be parsed.
+///
+#[must_use]
+pub fn sections(r: &str) -> Vec<Section> {
+    let Some(custom) = read(r) else {
+        return builtin().sections;
+    };
+    let mut sections: Vec<Section> = if custom.builtin {
+        builtin().sections
+    } else {
+        Vec::new()
+    };
+    for section in custom.sections {
+        match sections
+            .iter_mut()
+            .find(|x| x.title.eq_ignore_ascii_case(section.title.as_str()))
+        {
+            Some(x) => *x = section,
+            None => sections.push(section),
+        }
+    }
+    sections.retain(|x| {
+        custom
+            .remove
+            .iter()
+            .any(|t| x.title.eq_ignore_ascii_case(t))
+            .eq(&false)
+    });
+    sections
+}
+
+///
+/// A commit of the release with its parsed message.
+///
+#[derive(Clone, Debug)]
+pub struct Change {
+    pub id: String,
+    pub message: CommitMessage,
+}
+
+///
+/// The changes of a section, grouped by scope, the changes without scope using an empty scope.
+///
+#[derive(Clone, Debug)]
+pub struct Group {
+    pub title: String,
+    pub keep: String,
+    pub scopes: BTreeMap<String, Vec<Change>>,
+}
+
+///
+/// The changes made since the previous version tag.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Release {
+    pub previous: Option<String>,
+    pub breaking: Vec<(Change, String)>,
+    pub groups: Vec<Group>,
+}
+
+impl Change {
+    fn context(&self) -> Value {
+        let m: &CommitMessage = &self.message;
+        Value::from([
+            ("id", Value::from(self.id.as_str())),
+            ("type", Value::from(m.commit_type.as_str())),
+            ("scope", Value::from(m.scope.as_str())),
+            ("summary", Value::from(m.summary.as_str())),
+            ("why", Value::from(m.why.as_slice())),
+            ("breaking", Value::from(m.breaking.as_slice())),
+            ("fixes", Value::from(m.fixes.as_slice())),
+            ("closes", Value::from(m.closes.as_slice())),
+            ("author", Value::from(m.author.as_str())),
+            ("email", Value::from(m.email.as_str())),
+            ("date", Value::from(m.date.as_str())),
+        ])
+    }
+}
+
+impl Release {
+    ///
+    /// The authors of the changes with their number of commits, in the order of their first change.
+    ///
+    #[must_use]
+    pub fn contributors(&self) -> Vec<(String, String, usize)> {
+        let mut contributors: Vec<(String, String, usize)> = Vec::new();
+        for c in self
+            .groups
+            .iter()
+            .flat_map(|g| g.scopes.values().flatten())
+            .filter(|c| c.message.author.is_empty().eq(&false))
+        {
+            let m: &CommitMessage = &c.message;
+            match contributors
+                .iter_mut()
+                .find(|(name, email, _)| name.eq(&m.author) && email.eq(&m.email))
+            {
+                Some((_, _, n)) => *n += 1,
+                None => contributors.push((m.author.clone(), m.email.clone(), 1)),
+            }
+        }
+        contributors
+    }
+
+    ///
+    /// A release having every part, used to check the templates.
+    ///
+    #[must_use]
+    pub fn sample() -> Self {
+        let change: Change = Change {
+            id: String::from("0000000"),
+            message: CommitMessage::sample(),
+        };
+        Self {
+            previous: Some(String::from("v0.1.0")),
+            breaking: vec![(change.clone(), String::from("breaking"))],
+            groups: vec![Group {
+                title: String::from("Features"),
+                keep: String::from("Added"),
+                scopes: BTreeMap::from([(String::from("scope"), vec![change])]),
+            }],
+        }
+    }
+}
+
+///
+/// The metadata of the released package and its links.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Project {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub kind: String,
+    pub keywords: Vec<String>,
+    pub authors: Vec<String>,
+    pub readme: String,
+    pub license: String,
+    pub links: Vec<(&'static str, String)>,
+}
+
+/// The links of a project in the announcement context.
+pub const LINKS: [&str; 9] = [
+    "repository",
+    "homepage",
+    "issues",
+    "pulls",
+    "discussions",
+    "wiki",
+    "projects",
+    "releases",
+    "crate",
+];
+
+impl Project {
+    ///
+    /// A project having every part, used to check the templates.
+    ///
+    #[must_use]
+    pub fn sample() -> Self {
+        let one = |x: &str| vec![x.to_string()];
+        Self {
+            name: String::from("name"),
+            version: String::from("version"),
+            description: String::from("description"),
+            kind: String::from("library"),
+            keywords: one("keyword"),
+            authors: one("author"),
+            readme: String::from("readme"),
+            license: String::from("license"),
+            links: LINKS.iter().map(|l| (*l, format!("https://{l}"))).collect(),
+        }
+    }
+}
+
+///
+/// The context of the announcement of the `release` of `project` the `date`.
+///
+/// `project` gives `name`, `version`, `description`, `kind`, `keywords`, `authors`, `readme` and `license`, `links`
+/// gives the urls of the project, `contributors` the `name`, `email` and number of `commits` of the authors of the
+/// changes, `breaking` the `description` of each breaking change with the fields of its commit and `sections` the
+/// `title` and `keep` heading of each section with its `scopes`, each one having a `name` and its `changes`. A
+/// change gives `id`, `type`, `scope`, `summary`, `why`, `breaking`, `fixes`, `closes`, `author`, `email` and `date`.
+///
+#[must_use]
+pub fn announcement(project: &Project, release: &Release, date: &str) -> Value {
+    let links: Value = Value::Map(
+        project
+            .links
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), Value::from(v.as_str())))
+            .collect(),
+    );
+    let breaking: Vec<Value> = release
+        .breaking
+        .iter()
+        .map(|(c, b)| {
+            let mut v: Value = c.context();
+            if let Value::Map(m) = &mut v {
+                m.insert(String::from("description"), Value::from(b.as_str()));
+            }
+            v
+        })
+        .collect();
+    let sections: Vec<Value> = release
+        .groups
+        .iter()
+        .map(|g| {
+            Value::from([
+                ("title", Value::from(g.title.as_str())),
+                ("keep", Value::from(g.keep.as_str())),
+                (
+                    "scopes",
+                    Value::List(
+                        g.scopes
+                            .iter()
+                            .map(|(name, changes)| {
+                                Value::from([
+                                    ("name", Value::from(name.as_str())),
+                                    (
+                                        "changes",
+                                        Value::List(changes.iter().map(Change::context).collect()),
+                                    ),
+                                ])
+                            })
+                            .collect(),
+                    ),
+                ),
+            ])
+        })
+        .collect();
+    let contributors: Vec<Value> = release
+        .contributors()
+        .into_iter()
+        .map(|(name, email, commits)| {
+            Value::from([
+                ("name", Value::from(name)),
+                ("email", Value::from(email)),
+                ("commits", Value::from(commits.to_string())),
+            ])
+        })
+        .collect();
+    Value::from([
+        (
+            "project",
+            Value::from([
+                ("name", Value::from(project.name.as_str())),
+                ("version", Value::from(project.version.as_str())),
+                ("description", Value::from(project.description.as_str())),
+                ("kind", Value::from(project.kind.as_str())),
+                ("keywords", Value::from(project.keywords.as_slice())),
+                ("authors", Value::from(project.authors.as_slice())),
+                ("readme", Value::from(project.readme.as_str())),
+                ("license", Value::from(project.license.as_str())),
+            ]),
+        ),
+        ("links", links),
+        ("date", Value::from(date)),
+        (
+            "previous",
+            Value::from(release.previous.clone().unwrap_or_default()),
+        ),
+        ("contributors", Value::List(contributors)),
+        ("breaking", Value::List(breaking)),
+        ("sections", Value::List(sections)),
+    ])
+}
+
+///
+/// The version of the version tag `name`, like `1.2.0` for `v1.2.0` or `core-v1.2.0`.
+///
+#[must_use]
+pub fn tag_version(name: &str) -> &str {
+    VERSION_TAG
+        .captures(name)
+        .and_then(|c| c.name("version"))
+        .map_or(name, |v| v.as_str())
+}
+
+///
+/// The tag of `version` named like the `previous` tag, e.g. `v1.2.0`, `1.2.0`, `core-v1.2.0` or `core@1.2.0`, `v1.2.0`
+/// without a previous tag.
+///
+#[must_use]
+pub fn tag_name(previous: Option<&str>, version: &str) -> String {
+    previous
+        .and_then(|p| VERSION_TAG.captures(p))
+        .and_then(|c| c.name("version").map(|v| c[0][..v.start()].to_string()))
+        .map_or_else(
+            || format!("v{version}"),
+            |prefix| format!("{prefix}{version}"),
+        )
+}
+
+///
+/// The version tags of `repo` by commit.
+///
+/// The tags named like `1.2.3` or `v1.2.3` are the tags of the repository. A member of a workspace uses the tags
+/// prefixed by its name, like `core-v1.2.3` or `core@1.2.3`, or the tags of the repository when it has none.
+///
+fn version_tags(repo: &Repository, member: Option<&Member>) -> HashMap<Oid, String> {
+    let mut tags: HashMap<Oid, String> = HashMap::new();
+    let Ok(names) = repo.tag_names(None) else {
+        return tags;
+    };
+    let names: Vec<&str> = names.iter().flatten().collect();
+    let prefixed = |prefix: Option<&str>| -> Vec<&str> {
+        names
+            .iter()
+            .copied()
+            .filter(|n| {
+                VERSION_TAG
+                    .captures(n)
+                    .is_some_and(|c| c.name("package").map(|p| p.as_str()).eq(&prefix))
+            })
+            .collect()
+    };
+    let mut found: Vec<&str> = member.map_or_else(Vec::new, |m| prefixed(Some(m.name.as_str())));
+    if found.is_empty() {
+        found = prefixed(None);
+    }
+    for name in found {
+        if let Ok(commit) = repo
+            .revparse_single(format!("refs/tags/{name}").as_str())
+            .and_then(|o| o.peel_to_commit())
+        {
+            tags.insert(commit.id(), name.to_string());
+        }
+    }
+    tags
+}
+
+///
+/// The version tags of `member` reachable from `head` with their commit, the newest first.
+///
+/// # Errors
+///
+/// If the history cannot be walked.
+///
+pub fn tags(
+    repo: &Repository,
+    head: Oid,
+    member: Option<&Member>,
+) -> Result<Vec<(String, Oid)>, git2::Error> {
+    let tags: HashMap<Oid, String> = version_tags(repo, member);
+    let mut revwalk: Revwalk<'_> = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL)?;
+    revwalk.push(head)?;
+    let mut found: Vec<(String, Oid)> = Vec::new();
+    for oid in revwalk {
+        let oid: Oid = oid?;
+        if let Some(name) = tags.get(&oid) {
+            found.push((name.clone(), oid));
+        }
+    }
+    Ok(found)
+}
+
+///
+/// The newest version tag of `member` reachable from HEAD with its commit, the tags of HEAD itself being skipped so
+/// a tagged release lists its own changes.
+///
+/// # Errors
+///
+/// If the history cannot be walked.
+///
+pub fn previous_tag(
+    repo: &Repository,
+    member: Option<&Member>,
+) -> Result<Option<(String, Oid)>, git2::Error> {
+    let head: Oid = repo.head()?.peel_to_commit()?.id();
+    Ok(tags(repo, head, member)?
+        .into_iter()
+        .find(|(_, oid)| oid.ne(&head)))
+}
+
+///
+/// Whether `commit` changes the `directory` of the repository.
+///
+fn touches(commit: &Commit<'_>, directory: &Path) -> bool {
+    let entry = |c: &Commit<'_>| {
+        c.tree()
+            .ok()
+            .and_then(|t| t.get_path(directory).ok().map(|e| e.id()))
+    };
+    commit.parent(0).map_or_else(
+        |_| entry(commit).is_some(),
+        |p| entry(&p).ne(&entry(commit)),
+    )
+}
+
+///
+/// The commits of `head` not reachable from `since` changing `directory`, or any directory when `None`, the newest
+/// first, the merges being skipped.
+///
+/// # Errors
+///
+/// If the history cannot be walked.
+///
+pub fn commits_since<'r>(
+    repo: &'r Repository,
+    head: Oid,
+    since: Option<Oid>,
+    directory: Option<&Path>,
+) -> Result<Vec<Commit<'r>>, git2::Error> {
+    let mut revwalk: Revwalk<'_> = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL)?;
+    revwalk.push(head)?;
+    if let Some(oid) = since {
+        revwalk.hide(oid)?;
+    }
+    let mut commits: Vec<Commit<'_>> = Vec::new();
+    for oid in revwalk {
+        let commit: Commit<'_> = repo.find_commit(oid?)?;
+        if commit.parent_count().le(&1) && directory.is_none_or(|d| touches(&commit, d)) {
+            commits.push(commit);
+        }
+    }
+    Ok(commits)
+}
+
+///
+/// The title of the section of the commit type `t`.
+///
+fn section_of(
+    sections: &[Section],
+    types: &[CommitType],
+    translator: &Translator,
+    t: &str,
+) -> String {
+    let cosmic: String = commit_types::find(types, t)
+        .map(|x| x.name.clone())
+        .or_else(|| translator.cosmic(t))
+        .unwrap_or_else(|| t.to_string());
+    let conventional: String = translator
+        .conventional(cosmic.as_str())
+        .unwrap_or_else(|| t.to_lowercase());
+    sections
+        .iter()
+        .find(|s| {
+            s.types
+                .iter()
+                .any(|x| x.eq_ignore_ascii_case(cosmic.as_str()))
+        })
+        .or_else(|| {
+            sections.iter().find(|s| {
+                s.conventional
+                    .iter()
+                    .any(|x| x.eq_ignore_ascii_case(conventional.as_str()))
+            })
+        })
+        .map_or_else(|| String::from(OTHER_CHANGES), |s| s.title.clone())
+}
+
+///
+/// Group the commits of HEAD made since the previous version tag of the repository `r` by section and by scope.
+///
+/// For a `member` of a workspace only its version tags and the commits changing its directory are used.
+///
+/// The commit types are written in `style` when given, the messages not following the cosmic or the Conventional
+/// Commits format, like the fixup commits, are listed in the "Other changes" section with their subject.
+///
+/// # Errors
+///
+/// If the commit types file cannot be read or parsed, or the history cannot be walked.
+///
+pub fn release(
+    r: &str,
+    repo: &Repository,
+    style: Option<Style>,
+    member: Option<&Member>,
+) -> Result<Release, String> {
+    let grouping: Grouping = Grouping::new(r, style, member)?;
+    let walk = || -> Result<Release, git2::Error> {
+        let head: Oid = repo.head()?.peel_to_commit()?.id();
+        grouping.release(repo, head, previous_tag(repo, member)?)
+    };
+    walk().map_err(|e| e.to_string())
+}
+
+///
+/// A version of the history with its date and its changes.
+///
+#[derive(Clone, Debug)]
+pub struct Version {
+    pub name: String,
+    pub tag: Option<String>,
+    pub date: DateTime<FixedOffset>,
+    pub release: Release,
+}
+
+///
+/// Every version of the repository `r`, the newest first, each version tag listing the commits made since the tag
+/// before it.
+///
+/// The commits of HEAD made since the last version tag are listed under `current`, the version of the package, when
+/// there are some and no tag has this version. For a `member` of a workspace only its version tags and the commits
+/// changing its directory are used.
+///
+/// # Errors
+///
+/// If the commit types file cannot be read or parsed, or the history cannot be walked.
+///
+pub fn history(
+    r: &str,
+    repo: &Repository,
+    style: Option<Style>,
+    member: Option<&Member>,
+    current: &str,
+) -> Result<Vec<Version>, String> {
+    let grouping: Grouping = Grouping::new(r, style, member)?;
+    versions(repo, &grouping, member, current).map_err(|e| e.to_string())
+}
+
+fn versions(
+    repo: &Repository,
+    grouping: &Grouping,
+    member: Option<&Member>,
+    current: &str,
+) -> Result<Vec<Version>, git2::Error> {
+    let head: Commit<'_> = repo.head()?.peel_to_commit()?;
+    let tags: Vec<(String, Oid)> = tags(repo, head.id(), member)?;
+    let mut versions: Vec<Version> = Vec::new();
+    let tagged: bool = tags
+        .iter()
+        .any(|(name, _)| tag_version(name).eq(current.trim_start_matches('v')));
+    if tags.first().is_none_or(|(_, oid)| oid.ne(&head.id())) && tagged.eq(&false) {
+        let release: Release = grouping.release(repo, head.id(), tags.first().cloned())?;
+        if release.groups.is_empty().eq(&false) {
+            versions.push(Version {
+                name: current.to_string(),
+                tag: None,
+                date: time(&head),
+                release,
+            });
+        }
+    }
+    for (i, (name, oid)) in tags.iter().enumerate() {
+        let commit: Commit<'_> = repo.find_commit(*oid)?;
+        versions.push(Version {
+            name: tag_version(name).to_string(),
+            tag: Some(name.clone()),
+            date: time(&commit),
+            release: grouping.release(repo, *oid, tags.get(i + 1).cloned())?,
+        });
+    }
+    Ok(versions)
+}
+
+fn time(commit: &Commit<'_>) -> DateTime<FixedOffset> {
+    let t: git2::Time = commit.time();
+    FixedOffset::east_opt(t.offset_minutes() * 60)
+        .and_then(|o| DateTime::from_timestamp(t.seconds(), 0).map(|d| d.with_timezone(&o)))
+        .unwrap_or_default()
+}
+
+///
+/// The sections, the commit types and the translator grouping the commits of a repository, and the directory of
+/// the workspace member whose commits are grouped.
+///
+struct Grouping {
+    sections: Vec<Section>,
+    types: Vec<CommitType>,
+    translator: Translator,
+    style: Option<Style>,
+    directory: Option<PathBuf>,
+}
+
+impl Grouping {
+    fn new(r: &str, style: Option<Style>, member: Option<&Member>) -> Result<Self, String> {
+        Ok(Self {
+            sections: sections(r),
+            types: commit_types::load(r)?,
+            translator: Translator::new(r)?,
+            style,
+            directory: member.map(|m| m.directory.clone()),
+        })
+    }
+
+    ///
+    /// Group the commits of `head` not reachable from the `previous` tag by section and by scope.
+    ///
+    fn release(
+        &self,
+        repo: &Repository,
+        head: Oid,
+        previous: Option<(String, Oid)>,
+    ) -> Result<Release, git2::Error> {
+        let mut grouped: HashMap<String, BTreeMap<String, Vec<Change>>> = HashMap::new();
+        let mut breaking: Vec<(Change, String)> = Vec::new();
+        for commit in commits_since(
+            repo,
+            head,
+            previous.as_ref().map(|(_, oid)| *oid),
+            self.directory.as_deref(),
+        )? {
+            let id: String = commit.id().to_string().chars().take(7).collect();
+            let (title, message) = match CommitMessage::from_commit(&commit) {
+                Some(mut m) => {
+                    let title: String = section_of(
+                        &self.sections,
+                        &self.types,
+                        &self.translator,
+                        m.commit_type.as_str(),
+                    );
+                    if let Some(style) = self.style {
+                        self.translator.translate(&mut m, style);
+                    }
+                    (title, m)
+                }
+                None => (
+                    String::from(OTHER_CHANGES),
+                    CommitMessage {
+                        summary: rewrite::subject(&commit),
+                        author: commit.author().name().unwrap_or_default().to_string(),
+                        email: commit.author().email().unwrap_or_default().to_string(),
+                        ..CommitMessage::default()
+                    },
+                ),
+            };
+            let change: Change = Change { id, message };
+            for b in &change.message.breaking {
+                breaking.push((change.clone(), b.clone()));
+            }
+            grouped
+                .entry(title)
+                .or_default()
+                .entry(change.message.scope.clone())
+                .or_default()
+                .push(change);
+        }
+        let groups: Vec<Group> = self
+            .sections
+            .iter()
+            .map(|s| (s.title.clone(), s.keep.clone()))
+            .chain(std::iter::once((String::from(OTHER_CHANGES), changed())))
+            .filter_map(|(title, keep)| {
+                grouped.remove(&title).map(|scopes| Group {
+                    title,
+                    keep,
+                    scopes,
+                })
+            })
+            .collect();
+        Ok(Release {
+            previous: previous.map(|(name, _)| name),
+            breaking,
+            groups,
+        })
+    }
+}
+
+///
+/// The name of the version of the heading `line`, like `## [1.2.0] - 2024-01-31`, `None` for the other lines.
+///
+fn heading_version(line: &str) -> Option<&str> {
+    let heading: &str = line.strip_prefix("## ")?;
+    let name: &str = heading.split(" - ").next().unwrap_or_default().trim();
+    Some(name.trim_start_matches('[').trim_end_matches(']'))
+}
+
+fn same_version(a: &str, b: &str) -> bool {
+    a.trim_start_matches('v')
+        .eq_ignore_ascii_case(b.trim_start_matches('v'))
+}
+
+fn is_link(line: &str) -> bool {
+    line.starts_with('[') && line.contains("]: ")
+}
+
+///
+/// The lines of the entries `text` under each of their `### ` heading, the lines before the first heading being under
+/// an empty heading.
+///
+fn by_heading(text: &str) -> Vec<(String, Vec<String>)> {
+    let mut groups: Vec<(String, Vec<String>)> = vec![(String::new(), Vec::new())];
+    for line in text.lines() {
+        if let Some(h) = line.strip_prefix("### ") {
+            groups.push((h.trim().to_string(), Vec::new()));
+        } else if let Some((_, lines)) = groups.last_mut() {
+            lines.push(line.to_string());
+        }
+    }
+    for (_, lines) in &mut groups {
+        while lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.pop();
+        }
+        while lines.first().is_some_and(|l| l.trim().is_empty()) {
+            lines.remove(0);
+        }
+    }
+    groups
+}
+
+///
+/// Merge the hand-written entries `written` into the generated `entries`, the entries of a same heading being listed
+/// under a single heading, the generated ones first, in the Keep a Changelog order of the headings.
+///
+fn merge_entries(entries: &str, written: &str) -> String {
+    let mut headings: Vec<String> = Vec::new();
+    let mut merged: HashMap<String, Vec<String>> = HashMap::new();
+    for (heading, lines) in by_heading(entries).into_iter().chain(by_heading(written)) {
+        if lines.is_empty() {
+            continue;
+        }
+        let key: String = KEEP_HEADINGS
+            .iter()
+            .find(|k| k.eq_ignore_ascii_case(heading.as_str()))
+            .map_or(heading, |k| (*k).to_string());
+        if headings.contains(&key).eq(&false) {
+            headings.push(key.clone());
+        }
+        merged.entry(key).or_default().extend(lines);
+    }
+    let rank = |h: &String| {
+        KEEP_HEADINGS
+            .iter()
+            .position(|k| k.eq(h))
+            .unwrap_or(KEEP_HEADINGS.len())
+    };
+    headings.sort_by_key(|h| (h.is_empty().eq(&false), rank(h)));
+    let mut out: Vec<String> = Vec::new();
+    for heading in headings {
+        let lines: String = merged.remove(&heading).unwrap_or_default().join("\n");
+        if heading.is_empty() {
+            out.push(lines);
+        } else {
+            out.push(format!("### {heading}\n\n{lines}"));
+        }
+    }
+    out.join("\n\n")
+}
+
+///
+/// The changes of `release` under the Keep a Changelog headings, each breaking change being listed under its commit.
+///
+#[must_use]
+pub fn keep_a_changelog_entries(release: &Release) -> String {
+    let mut headings: Vec<&str> = KEEP_HEADINGS.to_vec();
+    for g in &release.groups {
+        if headings.contains(&g.keep.as_str()).eq(&false) {
+            headings.push(g.keep.as_str());
+        }
+    }
+    let mut out: String = String::new();
+    for heading in headings {
+        let changes: Vec<&Change> = release
+            .groups
+            .iter()
+            .filter(|g| g.keep.eq(heading))
+            .flat_map(|g| g.scopes.values().flatten())
+            .collect();
+        if changes.is_empty() {
+            continue;
+        }
+        out.push_str(format!("### {heading}\n\n").as_str());
+        for c in changes {
+            let m: &CommitMessage = &c.message;
+            if m.scope.is_empty() {
+                out.push_str(format!("- {} ({})\n", m.summary, c.id).as_str());
+            } else {
+                out.push_str(format!("- **{}**: {} ({})\n", m.scope, m.summary, c.id).as_str());
+            }
+            for b in &m.breaking {
+                out.push_str(format!("  - **Breaking**: {b}\n").as_str());
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+///
+/// Add the section of `version` released the `date` with `entries` to the Keep a Changelog `existing` content, a new
+/// changelog being started when `existing` is `None`.
+///
+/// The new section is inserted under the `Unreleased` section, which is emptied, its hand-written entries being merged
+/// into the headings of the new section. The older sections are kept as they are. The `links`, the compare urls of
+/// `Unreleased` and of the new version, replace the `Unreleased` link at the end of the changelog.
+///
+/// # Errors
+///
+/// If the changelog already has a section for `version`.
+///
+pub fn keep_a_changelog(
+    existing: Option<&str>,
+    version: &str,
+    date: &str,
+    entries: &str,
+    links: Option<(String, String)>,
+) -> Result<String, String> {
+    let content: String = existing.map_or_else(
+        || format!("{KEEP_HEADER}\n## [{UNRELEASED}]\n"),
+        String::from,
+    );
+    let mut lines: Vec<&str> = content.lines().collect();
+    if lines
+        .iter()
+        .filter_map(|l| heading_version(l))
+        .any(|v| same_version(v, version))
+    {
+        return Err(format!("{version} is already in {CHANGELOG_MD}"));
+    }
+    let mut end: usize = lines.len();
+    while end.gt(&0) && (lines[end - 1].trim().is_empty() || is_link(lines[end - 1])) {
+        end -= 1;
+    }
+    let mut references: Vec<&str> = lines.split_off(end);
+    references.retain(|l| l.trim().is_empty().eq(&false));
+    let unreleased: usize = if let Some(i) = lines
+        .iter()
+        .position(|l| heading_version(l).is_some_and(|v| v.eq_ignore_ascii_case(UNRELEASED)))
+    {
+        i
+    } else {
+        let i: usize = lines
+            .iter()
+            .position(|l| heading_version(l).is_some())
+            .unwrap_or(lines.len());
+        lines.insert(i, "");
+        lines.insert(i, "## [Unreleased]");
+        i
+    };
+    let next: usize = lines
+        .iter()
+        .skip(unreleased + 1)
+        .position(|l| heading_version(l).is_some())
+        .map_or(lines.len(), |p| p + unreleased + 1);
+    let written: String = lines[unreleased + 1..next].join("\n");
+    let section: String = format!(
+        "## [{version}] - {date}\n\n{}",
+        merge_entries(entries, written.as_str())
+    );
+    let mut out: Vec<String> = lines[..=unreleased]
+        .iter()
+        .map(|l| (*l).to_string())
+        .collect();
+    out.push(String::new());
+    out.push(section);
+    if next.lt(&lines.len()) {
+        out.push(String::new());
+    }
+    out.extend(lines[next..].iter().map(|l| (*l).to_string()));
+    if let Some((unreleased_link, version_link)) = links {
+        references.retain(|l| {
+            l.to_lowercase()
+                .starts_with(format!("[{}]:", UNRELEASED.to_lowercase()).as_str())
+                .eq(&false)
+        });
+        let mut all: Vec<String> = vec![
+            format!("[{}]: {unreleased_link}", UNRELEASED.to_lowercase()),
+            format!("[{version}]: {version_link}"),
+        ];
+        all.extend(references.iter().map(|l| (*l).to_string()));
+        out.push(String::new());
+        out.extend(all);
+    } else if references.is_empty().eq(&false) {
+        out.push(String::new());
+        out.extend(references.iter().map(|l| (*l).to_string()));
+    }
+    Ok(format!("{}\n", out.join("\n").trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTRIES: &str = "### Added\n\n- parse the config (abc1234)\n\n### Fixed\n\n- crash on empty input (def5678)\n\n";
+
+    fn links() -> Option<(String, String)> {
+        Some((
+            "https://example.com/compare/v1.0.0...HEAD".to_string(),
+            "https://example.com/compare/v0.9.0...v1.0.0".to_string(),
+        ))
+    }
+
+    #[test]
+    fn a_new_file_gets_the_header_the_section_and_the_links() {
+        let out: String =
+            keep_a_changelog(None, "1.0.0", "2024-05-01", ENTRIES, links()).expect("changelog");
+        assert!(out.starts_with(KEEP_HEADER));
+        assert!(out.contains(
+            "## [Unreleased]\n\n## [1.0.0] - 2024-05-01\n\n### Added\n\n- parse the config (abc1234)\n\n### Fixed\n\n- crash on empty input (def5678)\n"
+        ));
+        assert!(out.ends_with(
+            "[unreleased]: https://example.com/compare/v1.0.0...HEAD\n[1.0.0]: https://example.com/compare/v0.9.0...v1.0.0\n"
+        ));
+    }
+
+    #[test]
+    fn the_unreleased_entries_are_merged_under_the_headings() {
+        let existing: &str = "# Changelog\n\n## [Unreleased]\n\nA note on the release.\n\n### Fixed\n\n- hand-written fix\n\n### Security\n\n- patched the parser\n\n## [0.9.0] - 2024-01-01\n\n### Added\n\n- first release\n";
+        let out: String = keep_a_changelog(Some(existing), "1.0.0", "2024-05-01", ENTRIES, None)
+            .expect("changelog");
+        assert_eq!(out.matches("### Fixed").count(), 1);
+        assert_eq!(out.matches("### Added").count(), 2);
+        assert!(out.contains(
+            "## [Unreleased]\n\n## [1.0.0] - 2024-05-01\n\nA note on the release.\n\n### Added\n\n- parse the config (abc1234)\n\n### Fixed\n\n- crash on empty input (def5678)\n- hand-written fix\n\n### Security\n\n- patched the parser\n\n## [0.9.0] - 2024-01-01\n"
+        ));
+    }
+
+    #[test]
+    fn a_released_version_is_refused() {
+        let existing: &str =
+            "# Changelog\n\n## [Unreleased]\n\n## [v1.0.0] - 2024-01-01\n\n- first release\n";
+        assert!(keep_a_changelog(Some(existing), "1.0.0", "2024-05-01", ENTRIES, None).is_err());
+    }
+
+    #[test]
+    fn the_existing_links_are_kept() {
+        let existing: &str = "# Changelog\n\n## [Unreleased]\n\n## [0.9.0] - 2024-01-01\n\n- first release\n\n[unreleased]: https://example.com/compare/v0.9.0...HEAD\n[0.9.0]: https://example.com/releases/tag/v0.9.0\n";
+        let out: String = keep_a_changelog(Some(existing), "1.0.0", "2024-05-01", ENTRIES, links())
+            .expect("changelog");
+        assert!(out.ends_with(
+            "- first release\n\n[unreleased]: https://example.com/compare/v1.0.0...HEAD\n[1.0.0]: https://example.com/compare/v0.9.0...v1.0.0\n[0.9.0]: https://example.com/releases/tag/v0.9.0\n"
+        ));
+        let kept: String = keep_a_changelog(Some(existing), "1.0.0", "2024-05-01", ENTRIES, None)
+            .expect("changelog");
+        assert!(kept.ends_with(
+            "[unreleased]: https://example.com/compare/v0.9.0...HEAD\n[0.9.0]: https://example.com/releases/tag/v0.9.0\n"
+        ));
+    }
+
+    #[test]
+    fn the_unreleased_heading_is_added_when_missing() {
+        let existing: &str = "# Changelog\n\n## [0.9.0] - 2024-01-01\n\n- first release\n";
+        let out: String = keep_a_changelog(Some(existing), "1.0.0", "2024-05-01", ENTRIES, None)
+            .expect("changelog");
+        assert!(out.starts_with(
+            "# Changelog\n\n## [Unreleased]\n\n## [1.0.0] - 2024-05-01\n\n### Added\n"
+        ));
+        assert!(out.contains("(def5678)\n\n## [0.9.0] - 2024-01-01\n\n- first release\n"));
+    }
+}
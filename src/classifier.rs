@@ -0,0 +1,365 @@
+use crate::commit_types::{self, CommitType};
+use git2::{Delta, Diff, DiffFormat};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::path::{Path, MAIN_SEPARATOR_STR};
+
+/// The per-repository rules, stored in the zazen directory.
+pub const CLASSIFIER_FILE: &str = "classifier.toml";
+
+const RULES: &str = include_str!("classifier.toml");
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+    Typechange,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct Rule {
+    name: String,
+    #[serde(rename = "type")]
+    commit_type: String,
+    reason: String,
+    #[serde(default = "default_weight")]
+    weight: usize,
+    #[serde(default)]
+    files: Vec<String>,
+    #[serde(default)]
+    any_file: Vec<String>,
+    #[serde(default)]
+    status: Vec<Status>,
+    added: Option<String>,
+    removed: Option<String>,
+    lines: Option<String>,
+    #[serde(default)]
+    deletions_only: bool,
+}
+
+const fn default_weight() -> usize {
+    10
+}
+
+#[derive(Deserialize)]
+struct Rules {
+    #[serde(default = "keep_builtin")]
+    builtin: bool,
+    #[serde(default)]
+    remove: Vec<String>,
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+const fn keep_builtin() -> bool {
+    true
+}
+
+///
+/// A commit type recommended for the changes, with the reasons of the rules choosing it.
+///
+#[derive(Clone, Debug)]
+pub struct Recommendation {
+    pub commit_type: CommitType,
+    pub score: usize,
+    pub reasons: Vec<String>,
+}
+
+impl Display for Recommendation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}) : {}",
+            self.commit_type.name,
+            self.score,
+            self.reasons.join(", ")
+        )
+    }
+}
+
+struct Change {
+    path: String,
+    status: Option<Status>,
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+fn builtin() -> Rules {
+    toml::from_str::<Rules>(RULES).expect("the classifier rules are invalid")
+}
+
+///
+/// Check that the globs and the regexes of `rule` compile.
+///
+fn check(rule: &Rule) -> Result<(), String> {
+    let name: &str = rule.name.as_str();
+    globs(name, &rule.files)?;
+    globs(name, &rule.any_file)?;
+    for pattern in [&rule.added, &rule.removed, &rule.lines]
+        .into_iter()
+        .flatten()
+    {
+        regex(name, pattern)?;
+    }
+    Ok(())
+}
+
+///
+/// The rules of `zazen/classifier.toml` in the repository `r`, `None` when it does not exist.
+///
+fn read(r: &str) -> Result<Option<Rules>, String> {
+    let path: String = format!("{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}{CLASSIFIER_FILE}");
+    if Path::new(path.as_str()).is_file().eq(&false) {
+        return Ok(None);
+    }
+    let content: String = read_to_string(path.as_str()).map_err(|e| format!("{path} : {e}"))?;
+    let rules: Rules = toml::from_str(content.as_str())
+        .map_err(|e| format!("{path} is not a valid classifier file : {e}"))?;
+    for rule in &rules.rules {
+        check(rule).map_err(|e| format!("{path} : {e}"))?;
+    }
+    Ok(Some(rules))
+}
+
+fn load(r: &str) -> Result<Vec<Rule>, String> {
+    let Some(custom) = read(r)? else {
+        return Ok(builtin().rules);
+    };
+    let mut rules: Vec<Rule> = if custom.builtin {
+        builtin().rules
+    } else {
+        Vec::new()
+    };
+    for rule in custom.rules {
+        rules.retain(|x| x.name.ne(&rule.name));
+        rules.push(rule);
+    }
+    rules.retain(|x| custom.remove.contains(&x.name).eq(&false));
+    Ok(rules)
+}
+
+fn status(delta: Delta) -> Option<Status> {
+    match delta {
+        Delta::Added | Delta::Untracked | Delta::Copied => Some(Status::Added),
+        Delta::Deleted => Some(Status::Deleted),
+        Delta::Modified => Some(Status::Modified),
+        Delta::Renamed => Some(Status::Renamed),
+        Delta::Typechange => Some(Status::Typechange),
+        _ => None,
+    }
+}
+
+fn changes(diff: &Diff<'_>) -> Vec<Change> {
+    let mut changes: Vec<Change> = diff
+        .deltas()
+        .map(|d| Change {
+            path: d
+                .new_file()
+                .path()
+                .or_else(|| d.old_file().path())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            status: status(d.status()),
+            added: Vec::new(),
+            removed: Vec::new(),
+        })
+        .collect();
+    let _ = diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+        let path: String = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if let Some(change) = changes.iter_mut().find(|c| c.path.eq(&path)) {
+            let content: String = String::from_utf8_lossy(line.content())
+                .trim_end()
+                .to_string();
+            match line.origin() {
+                '+' => change.added.push(content),
+                '-' => change.removed.push(content),
+                _ => {}
+            }
+        }
+        true
+    });
+    changes
+}
+
+fn globs(rule: &str, patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder: GlobSetBuilder = GlobSetBuilder::new();
+    for p in patterns {
+        let glob: Glob = GlobBuilder::new(p.as_str())
+            .literal_separator(true)
+            .build()
+            .map_err(|e| format!("the rule {rule} has an invalid glob {p} : {e}"))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("the rule {rule} has invalid globs : {e}"))
+}
+
+fn regex(rule: &str, pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("the rule {rule} has an invalid regex {pattern} : {e}"))
+}
+
+///
+/// Check if `rule` matches `changes`, the patterns of the rule having been checked when it was loaded.
+///
+fn matches(rule: &Rule, changes: &[Change]) -> Result<bool, String> {
+    let name: &str = rule.name.as_str();
+    if rule.files.is_empty().eq(&false) {
+        let set: GlobSet = globs(name, &rule.files)?;
+        if changes
+            .iter()
+            .any(|c| set.is_match(c.path.as_str()).eq(&false))
+        {
+            return Ok(false);
+        }
+    }
+    if rule.any_file.is_empty().eq(&false) {
+        let set: GlobSet = globs(name, &rule.any_file)?;
+        if changes
+            .iter()
+            .any(|c| set.is_match(c.path.as_str()))
+            .eq(&false)
+        {
+            return Ok(false);
+        }
+    }
+    if rule.status.is_empty().eq(&false)
+        && changes
+            .iter()
+            .any(|c| c.status.is_none_or(|s| rule.status.contains(&s).eq(&false)))
+    {
+        return Ok(false);
+    }
+    if let Some(added) = rule.added.as_deref() {
+        let re: Regex = regex(name, added)?;
+        if changes
+            .iter()
+            .flat_map(|c| &c.added)
+            .any(|l| re.is_match(l))
+            .eq(&false)
+        {
+            return Ok(false);
+        }
+    }
+    if let Some(removed) = rule.removed.as_deref() {
+        let re: Regex = regex(name, removed)?;
+        if changes
+            .iter()
+            .flat_map(|c| &c.removed)
+            .any(|l| re.is_match(l))
+            .eq(&false)
+        {
+            return Ok(false);
+        }
+    }
+    if let Some(lines) = rule.lines.as_deref() {
+        let re: Regex = regex(name, lines)?;
+        let mut changed = changes
+            .iter()
+            .flat_map(|c| c.added.iter().chain(&c.removed))
+            .filter(|l| l.trim().is_empty().eq(&false))
+            .peekable();
+        if changed.peek().is_none() || changed.any(|l| re.is_match(l).eq(&false)) {
+            return Ok(false);
+        }
+    }
+    if rule.deletions_only
+        && changes.iter().any(|c| {
+            c.added.is_empty().eq(&false)
+                || (c.removed.is_empty() && c.status.ne(&Some(Status::Deleted)))
+        })
+    {
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+///
+/// Recommend the commit types of the repository `r` for the changes of `diff`, the best first.
+///
+/// The rules of `zazen/classifier.toml` are merged with the builtin rules, a rule using the name of a builtin one
+/// replace it. The rules recommending a type missing from the catalogue are ignored.
+///
+/// # Errors
+///
+/// If the rules file or the commit types file cannot be read or parsed, or a rule has an invalid glob or regex, the
+/// error naming the file and the rule.
+///
+pub fn classify(r: &str, diff: &Diff<'_>) -> Result<Vec<Recommendation>, String> {
+    let rules: Vec<Rule> = load(r)?;
+    let changes: Vec<Change> = changes(diff);
+    if changes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let types: Vec<CommitType> = commit_types::load(r)?;
+    let mut scores: BTreeMap<String, Recommendation> = BTreeMap::new();
+    for rule in &rules {
+        if matches(rule, &changes)?.eq(&false) {
+            continue;
+        }
+        let Some(t) = commit_types::find(&types, rule.commit_type.as_str()) else {
+            continue;
+        };
+        let recommendation: &mut Recommendation =
+            scores
+                .entry(t.name.clone())
+                .or_insert_with(|| Recommendation {
+                    commit_type: t.clone(),
+                    score: 0,
+                    reasons: Vec::new(),
+                });
+        recommendation.score += rule.weight;
+        recommendation.reasons.push(rule.reason.clone());
+    }
+    let mut recommendations: Vec<Recommendation> = scores.into_values().collect();
+    recommendations.sort_by_key(|x| std::cmp::Reverse(x.score));
+    Ok(recommendations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Repository;
+    use std::fs;
+
+    #[test]
+    fn the_builtin_rules_are_valid() {
+        assert!(builtin().rules.iter().all(|rule| check(rule).is_ok()));
+    }
+
+    #[test]
+    fn invalid_patterns_are_reported_with_the_file_and_the_rule() {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let repo: Repository = Repository::init(dir.path()).expect("repository");
+        fs::create_dir_all(dir.path().join("zazen")).expect("zazen directory");
+        let r: &str = dir.path().to_str().expect("utf-8 path");
+        let diff: Diff<'_> = repo.diff_index_to_workdir(None, None).expect("diff");
+        for (rule, error) in [
+            ("files = [\"src/[\"]", "has an invalid glob src/["),
+            ("added = \"(\"", "has an invalid regex ("),
+        ] {
+            fs::write(
+                dir.path().join("zazen").join(CLASSIFIER_FILE),
+                format!(
+                    "[[rules]]\nname = \"broken\"\ntype = \"Star\"\nreason = \"broken\"\n{rule}\n"
+                ),
+            )
+            .expect("rules");
+            let message: String = classify(r, &diff).expect_err("invalid rule");
+            assert!(message.contains(CLASSIFIER_FILE));
+            assert!(message.contains(format!("the rule broken {error}").as_str()));
+        }
+    }
+}
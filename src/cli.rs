@@ -0,0 +1,247 @@
+use crate::changelog::Format;
+use crate::conventional::Style;
+use clap::{Args, Parser, Subcommand};
+
+/// Offer a better workflow for developer using git and rust.
+///
+/// Without a subcommand zazen asks for a repository inside `CRATES_PATH` and opens the interactive menu.
+#[derive(Parser)]
+#[command(name = "zazen", version, about)]
+pub struct Cli {
+    /// Run as if zazen was started in this directory
+    #[arg(short = 'C', long = "repo", global = true, default_value = ".")]
+    pub repo: String,
+
+    /// The package of the cargo workspace to use, the root package by default, prompted for in a virtual workspace
+    #[arg(long, global = true)]
+    pub package: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Action>,
+}
+
+#[derive(Subcommand)]
+pub enum Action {
+    /// Add a commit
+    Commit(CommitArgs),
+    /// Amend the last commit, the prompts are pre-filled with its message
+    Amend(AmendArgs),
+    /// Add a fixup commit for an unpushed commit
+    Fixup(FixupArgs),
+    /// Melt the fixup and squash commits into their target in the unpushed history
+    Autosquash,
+    /// Display the commit types of the repository
+    Types,
+    /// Check the templates of the repository
+    Templates,
+    /// Generate or update the changelog
+    Changelog {
+        /// Write the commit types in this syntax instead of the one used by the commits
+        #[arg(long, value_enum)]
+        style: Option<Style>,
+        /// The output to write: the announcement, CHANGELOG.md, or a JSON, HTML or Atom export
+        #[arg(long, value_enum, default_value_t = Format::Announcement)]
+        format: Format,
+    },
+    /// Manage the README.md
+    #[command(subcommand)]
+    Readme(ReadmeAction),
+    /// Manage the local branches
+    #[command(subcommand)]
+    Branches(RefAction),
+    /// Manage the tags
+    #[command(subcommand)]
+    Tags(RefAction),
+    /// Display the staged and the not staged changes
+    Status,
+    /// Display the diff of the working tree
+    Diff,
+    /// Remove files from the index, their changes are kept in the working tree
+    Unstage {
+        /// The files to unstage, prompt for them when none are given
+        files: Vec<String>,
+    },
+    /// Display how the commits are signed
+    Signing,
+    /// Check the commit messages with the lint rules of the repository
+    Lint {
+        /// The commits to check, like HEAD~20..HEAD or main...HEAD, the unpushed commits when none is given
+        range: Option<String>,
+    },
+    /// Display the last commits
+    Log {
+        /// Display the commit types in this syntax instead of the one used by the commits
+        #[arg(long, value_enum)]
+        style: Option<Style>,
+    },
+    /// Send modifications to remotes
+    Push,
+    /// Clone a repository inside CRATES_PATH
+    Clone {
+        /// The repository url
+        url: String,
+    },
+    /// Open the project in CRATES_EDITOR
+    Open,
+    /// Manage the commit-msg hook enforcing the cosmic format
+    #[command(subcommand)]
+    Hook(HookAction),
+}
+
+#[derive(Subcommand)]
+pub enum ReadmeAction {
+    /// Generate the README.md from the zazen/readme parts
+    Generate,
+    /// Check the words of the zazen/readme parts
+    Check,
+    /// Display the README.md
+    Display,
+}
+
+#[derive(Subcommand)]
+pub enum HookAction {
+    /// Install the commit-msg hook
+    Install,
+    /// Remove the commit-msg hook
+    Uninstall,
+    /// Display if the commit-msg hook is installed
+    Status,
+    /// Check a commit message, called by git
+    CommitMsg {
+        /// The file containing the commit message
+        file: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RefAction {
+    /// List the references
+    List,
+    /// Remove references, prompt for them when none are given
+    Rm {
+        /// The names to remove
+        names: Vec<String>,
+    },
+}
+
+#[derive(Args, Default)]
+pub struct CommitArgs {
+    /// The commit type, e.g. Star
+    #[arg(short = 't', long = "type")]
+    pub commit_type: Option<String>,
+
+    /// The commit scope
+    #[arg(short, long)]
+    pub scope: Option<String>,
+
+    /// The commit summary, the footer prompts are then skipped and the footer is built from the flags only
+    #[arg(short = 'm', long)]
+    pub summary: Option<String>,
+
+    /// A reasoning behind the change, can be repeated
+    #[arg(short, long)]
+    pub why: Vec<String>,
+
+    /// A breaking change description, can be repeated
+    #[arg(short, long)]
+    pub breaking: Vec<String>,
+
+    /// An issue number fixed by the commit, can be repeated
+    #[arg(long)]
+    pub fixes: Vec<String>,
+
+    /// An issue number closed by the commit, can be repeated
+    #[arg(long)]
+    pub closes: Vec<String>,
+
+    /// A co-author as Name <email>, can be repeated
+    #[arg(long)]
+    pub co_author: Vec<String>,
+
+    /// Add a Signed-off-by trailer, default to the commit.signoff of zazen/config.toml
+    #[arg(long)]
+    pub signoff: bool,
+
+    /// A trailer as Key: value, can be repeated
+    #[arg(long)]
+    pub trailer: Vec<String>,
+
+    /// The syntax of the commit type, default to the commit.style of zazen/config.toml
+    #[arg(long, value_enum)]
+    pub style: Option<Style>,
+
+    /// Stage every change of the working tree
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Pick the hunks or the lines of the modified files to stage
+    #[arg(short, long)]
+    pub patch: bool,
+
+    /// Pick the staged files to unstage before the files to add
+    #[arg(short, long)]
+    pub unstage: bool,
+
+    /// Review the message in EDITOR or CRATES_EDITOR before committing
+    #[arg(short, long)]
+    pub edit: bool,
+
+    /// Skip cargo fmt, zuu, the readme words check and the pre-commit and commit-msg hooks
+    #[arg(long)]
+    pub no_verify: bool,
+
+    /// The files to stage, prompt for them when none are given
+    pub files: Vec<String>,
+}
+
+#[derive(Args, Default)]
+pub struct AmendArgs {
+    /// The syntax of the commit type, default to the commit.style of zazen/config.toml
+    #[arg(long, value_enum)]
+    pub style: Option<Style>,
+
+    /// Stage every change of the working tree in the amended commit
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Review the message in EDITOR or CRATES_EDITOR before amending
+    #[arg(short, long)]
+    pub edit: bool,
+
+    /// Skip cargo fmt, zuu, the readme words check and the pre-commit and commit-msg hooks
+    #[arg(long)]
+    pub no_verify: bool,
+
+    /// The files to stage in the amended commit
+    pub files: Vec<String>,
+}
+
+#[derive(Args, Default)]
+pub struct FixupArgs {
+    /// The commit to fix, prompt for an unpushed commit when not given
+    #[arg(short, long)]
+    pub commit: Option<String>,
+
+    /// Add the reasons of the fixup to the reasons of the commit when squashed
+    #[arg(long)]
+    pub squash: bool,
+
+    /// Stage every change of the working tree
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Pick the hunks or the lines of the modified files to stage
+    #[arg(short, long)]
+    pub patch: bool,
+
+    /// Pick the staged files to unstage before the files to add
+    #[arg(short, long)]
+    pub unstage: bool,
+
+    /// Skip cargo fmt, zuu, the readme words check and the pre-commit and commit-msg hooks
+    #[arg(long)]
+    pub no_verify: bool,
+
+    /// The files to stage, prompt for them when none are given
+    pub files: Vec<String>,
+}
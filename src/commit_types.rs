@@ -0,0 +1,164 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::path::{Path, MAIN_SEPARATOR_STR};
+
+/// The per-repository catalogue, stored in the zazen directory.
+pub const COMMIT_TYPES_FILE: &str = "commit-types.toml";
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Semver {
+    #[default]
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Display for Semver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::None => "none",
+            Self::Patch => "patch",
+            Self::Minor => "minor",
+            Self::Major => "major",
+        })
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CommitType {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub emoji: String,
+    #[serde(default)]
+    pub semver: Semver,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub conventional: String,
+}
+
+impl CommitType {
+    ///
+    /// Check if the name or one of the aliases of the type match `t`.
+    ///
+    #[must_use]
+    pub fn is(&self, t: &str) -> bool {
+        let t: &str = t.trim();
+        self.name.eq_ignore_ascii_case(t) || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(t))
+    }
+}
+
+impl Display for CommitType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.emoji.is_empty() {
+            write!(f, "{}: {}", self.name, self.description)
+        } else {
+            write!(f, "{} {}: {}", self.emoji, self.name, self.description)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Catalogue {
+    #[serde(default = "keep_builtin")]
+    builtin: bool,
+    #[serde(default)]
+    remove: Vec<String>,
+    #[serde(default)]
+    types: Vec<CommitType>,
+    #[serde(default)]
+    conventional: BTreeMap<String, String>,
+}
+
+const fn keep_builtin() -> bool {
+    true
+}
+
+const COSMIC_TYPES: &str = include_str!("commit-types.toml");
+
+fn builtin() -> Catalogue {
+    toml::from_str::<Catalogue>(COSMIC_TYPES).expect("the cosmic commit types are invalid")
+}
+
+fn catalogue_path(r: &str) -> String {
+    format!("{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}{COMMIT_TYPES_FILE}")
+}
+
+///
+/// Load the commit types of the repository `r`.
+///
+/// The types of `zazen/commit-types.toml` are merged with the cosmic list, a type with the same name replace the
+/// cosmic one. Without the file the cosmic list is returned.
+///
+/// # Errors
+///
+/// If the catalogue file cannot be read or parsed, the error naming the file.
+///
+pub fn load(r: &str) -> Result<Vec<CommitType>, String> {
+    let Some(catalogue) = read(r)? else {
+        return Ok(sorted(builtin().types));
+    };
+    let mut types: Vec<CommitType> = if catalogue.builtin {
+        builtin().types
+    } else {
+        Vec::new()
+    };
+    for t in catalogue.types {
+        types.retain(|x| x.name.eq_ignore_ascii_case(t.name.as_str()).eq(&false));
+        types.push(t);
+    }
+    types.retain(|x| catalogue.remove.iter().any(|r| x.is(r)).eq(&false));
+    Ok(sorted(types))
+}
+
+///
+/// Load the cosmic type displayed for each Conventional Commits type of the repository `r`.
+///
+/// # Errors
+///
+/// If the catalogue file cannot be read or parsed, the error naming the file.
+///
+pub fn load_conventional(r: &str) -> Result<BTreeMap<String, String>, String> {
+    let mut table: BTreeMap<String, String> = builtin().conventional;
+    if let Some(catalogue) = read(r)? {
+        if catalogue.builtin.eq(&false) {
+            table.clear();
+        }
+        table.extend(catalogue.conventional);
+    }
+    Ok(table)
+}
+
+fn read(r: &str) -> Result<Option<Catalogue>, String> {
+    let path: String = catalogue_path(r);
+    if Path::new(path.as_str()).is_file().eq(&false) {
+        return Ok(None);
+    }
+    let content: String =
+        read_to_string(path.as_str()).map_err(|e| format!("failed to read {path} : {e}"))?;
+    toml::from_str(content.as_str())
+        .map(Some)
+        .map_err(|e| format!("{path} is not a valid commit types file : {e}"))
+}
+
+fn sorted(mut types: Vec<CommitType>) -> Vec<CommitType> {
+    types.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    types
+}
+
+///
+/// Find the type named `t`, or having `t` as alias.
+///
+#[must_use]
+pub fn find<'a>(types: &'a [CommitType], t: &str) -> Option<&'a CommitType> {
+    types
+        .iter()
+        .find(|x| x.name.eq_ignore_ascii_case(t.trim()))
+        .or_else(|| types.iter().find(|x| x.is(t)))
+}
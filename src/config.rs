@@ -0,0 +1,123 @@
+use crate::conventional::Style;
+use crate::template::config_dir;
+use globset::Glob;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::{Path, MAIN_SEPARATOR_STR};
+use toml::Table;
+
+/// The configuration file, in the zazen directory of the repository or of the user configuration.
+pub const CONFIG_FILE: &str = "config.toml";
+
+#[derive(Deserialize, Default)]
+pub struct Config {
+    #[serde(default)]
+    pub commit: CommitConfig,
+    #[serde(default)]
+    pub spelling: SpellingConfig,
+}
+
+#[derive(Deserialize, Default)]
+pub struct CommitConfig {
+    #[serde(default)]
+    pub style: Style,
+    #[serde(default)]
+    pub signoff: bool,
+}
+
+///
+/// The dictionaries of the spelling check, `readme` giving the languages of the readme parts matching a glob.
+///
+#[derive(Deserialize)]
+pub struct SpellingConfig {
+    #[serde(default = "default_languages")]
+    pub languages: Vec<String>,
+    #[serde(default)]
+    pub readme: BTreeMap<String, Vec<String>>,
+}
+
+fn default_languages() -> Vec<String> {
+    vec![String::from("en_US")]
+}
+
+impl Default for SpellingConfig {
+    fn default() -> Self {
+        Self {
+            languages: default_languages(),
+            readme: BTreeMap::new(),
+        }
+    }
+}
+
+impl SpellingConfig {
+    ///
+    /// The languages of the readme part `part`, relative to `zazen/readme`, the longest matching glob winning.
+    ///
+    #[must_use]
+    pub fn readme_languages(&self, part: &str) -> &[String] {
+        self.readme
+            .iter()
+            .filter(|(glob, _)| {
+                Glob::new(glob)
+                    .map(|g| g.compile_matcher().is_match(part))
+                    .unwrap_or(false)
+            })
+            .max_by_key(|(glob, _)| glob.len())
+            .map_or(self.languages.as_slice(), |(_, languages)| {
+                languages.as_slice()
+            })
+    }
+}
+
+///
+/// The table of the configuration file `path`, empty when the file does not exist.
+///
+/// # Errors
+///
+/// If the file cannot be read or parsed, the error naming the file.
+///
+pub fn read(path: &str) -> Result<Table, String> {
+    if Path::new(path).is_file().eq(&false) {
+        return Ok(Table::new());
+    }
+    read_to_string(path)
+        .map_err(|e| format!("failed to read {path} : {e}"))?
+        .parse::<Table>()
+        .map_err(|e| format!("{path} is not a valid configuration : {e}"))
+}
+
+///
+/// Merge `other` over `base`, the tables being merged key by key.
+///
+pub fn merge(base: &mut Table, other: Table) {
+    for (k, v) in other {
+        match (base.get_mut(k.as_str()), v) {
+            (Some(toml::Value::Table(b)), toml::Value::Table(o)) => merge(b, o),
+            (_, v) => {
+                base.insert(k, v);
+            }
+        }
+    }
+}
+
+///
+/// Load the configuration of the repository `r`, merged over the configuration of the user.
+///
+/// # Errors
+///
+/// If a configuration file cannot be read or parsed, or the merged configuration has invalid values.
+///
+pub fn load(r: &str) -> Result<Config, String> {
+    let mut table: Table = match config_dir() {
+        Some(d) => read(format!("{d}{MAIN_SEPARATOR_STR}{CONFIG_FILE}").as_str())?,
+        None => Table::new(),
+    };
+    merge(
+        &mut table,
+        read(format!("{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}{CONFIG_FILE}").as_str())?,
+    );
+    table
+        .try_into()
+        .map_err(|e| format!("the zazen configuration is invalid : {e}"))
+}
@@ -0,0 +1,112 @@
+use crate::commit_types::{self, CommitType};
+use crate::message::CommitMessage;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+///
+/// The syntax of the commit types.
+///
+#[derive(Deserialize, ValueEnum, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Style {
+    /// The cosmic types, e.g. Star
+    #[default]
+    Cosmic,
+    /// The Conventional Commits types, e.g. feat
+    Conventional,
+}
+
+///
+/// Translate the commit types between the cosmic and the Conventional Commits syntax.
+///
+pub struct Translator {
+    types: Vec<CommitType>,
+    table: BTreeMap<String, String>,
+}
+
+impl Translator {
+    ///
+    /// Build the translator of the repository `r`.
+    ///
+    /// # Errors
+    ///
+    /// If the commit types file cannot be read or parsed.
+    ///
+    pub fn new(r: &str) -> Result<Self, String> {
+        Ok(Self {
+            types: commit_types::load(r)?,
+            table: commit_types::load_conventional(r)?,
+        })
+    }
+
+    ///
+    /// The commit types of the catalogue.
+    ///
+    #[must_use]
+    pub fn types(&self) -> &[CommitType] {
+        &self.types
+    }
+
+    ///
+    /// The name of the cosmic type `t`, given by its name, an alias or a Conventional Commits type.
+    ///
+    #[must_use]
+    pub fn name(&self, t: &str) -> Option<String> {
+        commit_types::find(&self.types, t)
+            .map(|x| x.name.clone())
+            .or_else(|| self.cosmic(t))
+    }
+
+    ///
+    /// The Conventional Commits type of the cosmic type `t`.
+    ///
+    #[must_use]
+    pub fn conventional(&self, t: &str) -> Option<String> {
+        commit_types::find(&self.types, t)
+            .map(|x| x.conventional.clone())
+            .filter(|x| x.is_empty().eq(&false))
+    }
+
+    ///
+    /// The cosmic type of the Conventional Commits type `t`.
+    ///
+    #[must_use]
+    pub fn cosmic(&self, t: &str) -> Option<String> {
+        let t: &str = t.trim();
+        self.table
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(t))
+            .map(|(_, v)| v.clone())
+            .or_else(|| {
+                self.types
+                    .iter()
+                    .find(|x| x.conventional.eq_ignore_ascii_case(t))
+                    .map(|x| x.name.clone())
+            })
+    }
+
+    ///
+    /// Check if `t` is a cosmic type or a Conventional Commits type known by the catalogue.
+    ///
+    #[must_use]
+    pub fn knows(&self, t: &str) -> bool {
+        commit_types::find(&self.types, t).is_some() || self.cosmic(t).is_some()
+    }
+
+    ///
+    /// Write the type of `m` in the `style` syntax, unknown types are kept.
+    ///
+    pub fn translate(&self, m: &mut CommitMessage, style: Style) {
+        let translated: Option<String> = match style {
+            Style::Cosmic if commit_types::find(&self.types, m.commit_type.as_str()).is_none() => {
+                self.cosmic(m.commit_type.as_str())
+            }
+            Style::Conventional => self.conventional(m.commit_type.as_str()),
+            Style::Cosmic => None,
+        };
+        if let Some(t) = translated {
+            m.commit_type = t;
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::message::CommitMessage;
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, read_to_string};
+use std::path::PathBuf;
+
+/// The draft of the commit being written, in the zazen directory of the git directory.
+pub const DRAFT_FILE: &str = "draft.toml";
+
+///
+/// The steps of the commit message prompts.
+///
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Step {
+    Type,
+    Scope,
+    Summary,
+    Why,
+    Footer,
+}
+
+///
+/// A commit message interrupted before its commit, with the steps already answered.
+///
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct Draft {
+    #[serde(default)]
+    pub done: Vec<Step>,
+    #[serde(default)]
+    pub message: CommitMessage,
+}
+
+///
+/// The path of the draft of `repo`.
+///
+#[must_use]
+pub fn path(repo: &Repository) -> PathBuf {
+    repo.path().join("zazen").join(DRAFT_FILE)
+}
+
+///
+/// The draft of `repo`, `None` when there is none or it cannot be read.
+///
+#[must_use]
+pub fn load(repo: &Repository) -> Option<Draft> {
+    read_to_string(path(repo))
+        .ok()
+        .and_then(|d| toml::from_str(d.as_str()).ok())
+}
+
+///
+/// Delete the draft of `repo`.
+///
+pub fn discard(repo: &Repository) {
+    let _ = fs::remove_file(path(repo));
+}
+
+impl Draft {
+    ///
+    /// Check if `step` has been answered.
+    ///
+    #[must_use]
+    pub fn has(&self, step: Step) -> bool {
+        self.done.contains(&step)
+    }
+
+    ///
+    /// Mark `step` as answered and write the draft in `repo`.
+    ///
+    /// # Errors
+    ///
+    /// If the draft cannot be written.
+    ///
+    pub fn save(&mut self, repo: &Repository, step: Step) -> Result<(), String> {
+        if self.has(step).eq(&false) {
+            self.done.push(step);
+        }
+        let file: PathBuf = path(repo);
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("failed to create {} : {e}", dir.display()))?;
+        }
+        let content: String =
+            toml::to_string(self).map_err(|e| format!("failed to serialize the draft : {e}"))?;
+        fs::write(&file, content).map_err(|e| format!("failed to write {} : {e}", file.display()))
+    }
+}
@@ -0,0 +1,238 @@
+use crate::changelog::{Change, Project, Version};
+use atom_syndication::{Content, Entry, Feed, Link, Person};
+use serde_json::json;
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;line-height:1.5;max-width:60rem;margin:2rem auto;padding:0 1rem;color:#222}
+h2{border-bottom:1px solid #ddd;padding-bottom:.3rem}
+time{color:#666;font-size:.8em;font-weight:normal;margin-left:.5rem}
+code{background:#f3f3f3;padding:0 .3rem;border-radius:3px}
+.breaking{color:#b00020}";
+
+fn link<'a>(project: &'a Project, name: &str) -> Option<&'a str> {
+    project
+        .links
+        .iter()
+        .find(|(k, _)| k.eq(&name))
+        .map(|(_, v)| v.as_str())
+        .filter(|v| v.is_empty().eq(&false))
+}
+
+fn change_json(c: &Change) -> serde_json::Value {
+    let m = &c.message;
+    json!({
+        "id": c.id,
+        "type": m.commit_type,
+        "scope": m.scope,
+        "summary": m.summary,
+        "why": m.why,
+        "breaking": m.breaking,
+        "fixes": m.fixes,
+        "closes": m.closes,
+        "author": m.author,
+        "email": m.email,
+    })
+}
+
+///
+/// The versions of `project` as JSON, each version listing its breaking changes and its commits grouped by section.
+///
+/// # Panics
+///
+/// If the versions cannot be serialized.
+///
+#[must_use]
+pub fn json(project: &Project, versions: &[Version]) -> String {
+    let versions: Vec<serde_json::Value> = versions
+        .iter()
+        .map(|v| {
+            json!({
+                "version": v.name,
+                "tag": v.tag,
+                "date": v.date.to_rfc3339(),
+                "previous": v.release.previous,
+                "breaking": v.release.breaking.iter().map(|(c, b)| json!({
+                    "id": c.id,
+                    "scope": c.message.scope,
+                    "description": b,
+                })).collect::<Vec<serde_json::Value>>(),
+                "sections": v.release.groups.iter().map(|g| json!({
+                    "title": g.title,
+                    "keep": g.keep,
+                    "commits": g.scopes.values().flatten().map(change_json).collect::<Vec<serde_json::Value>>(),
+                })).collect::<Vec<serde_json::Value>>(),
+            })
+        })
+        .collect();
+    let document: serde_json::Value = json!({
+        "project": project.name,
+        "version": project.version,
+        "description": project.description,
+        "repository": link(project, "repository"),
+        "versions": versions,
+    });
+    format!(
+        "{}\n",
+        serde_json::to_string_pretty(&document).expect("failed to serialize the changelog")
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn change_html(c: &Change) -> String {
+    let m = &c.message;
+    let mut item: String = String::from("<li>");
+    if m.commit_type.is_empty().eq(&false) {
+        item.push_str(format!("<code>{}</code> ", escape(m.commit_type.as_str())).as_str());
+    }
+    item.push_str(escape(m.summary.as_str()).as_str());
+    item.push_str(format!(" <code>{}</code>", escape(c.id.as_str())).as_str());
+    for i in m.fixes.iter().chain(&m.closes) {
+        item.push_str(format!(" #{}", escape(i)).as_str());
+    }
+    if m.why.is_empty().eq(&false) {
+        item.push_str("<ul>");
+        for w in &m.why {
+            item.push_str(format!("<li>{}</li>", escape(w)).as_str());
+        }
+        item.push_str("</ul>");
+    }
+    item.push_str("</li>\n");
+    item
+}
+
+///
+/// The changes of `version` as an html fragment, the breaking changes first then each section grouped by scope.
+///
+fn version_html(version: &Version) -> String {
+    let mut html: String = String::new();
+    if version.release.breaking.is_empty().eq(&false) {
+        html.push_str("<h3 class=\"breaking\">Breaking changes</h3>\n<ul>\n");
+        for (c, b) in &version.release.breaking {
+            let scope: String = if c.message.scope.is_empty() {
+                String::new()
+            } else {
+                format!("<strong>{}</strong>: ", escape(c.message.scope.as_str()))
+            };
+            html.push_str(
+                format!(
+                    "<li>{scope}{} <code>{}</code></li>\n",
+                    escape(b),
+                    escape(c.id.as_str())
+                )
+                .as_str(),
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+    for g in &version.release.groups {
+        html.push_str(format!("<h3>{}</h3>\n", escape(g.title.as_str())).as_str());
+        for (scope, changes) in &g.scopes {
+            if scope.is_empty().eq(&false) {
+                html.push_str(format!("<h4>{}</h4>\n", escape(scope)).as_str());
+            }
+            html.push_str("<ul>\n");
+            for c in changes {
+                html.push_str(change_html(c).as_str());
+            }
+            html.push_str("</ul>\n");
+        }
+    }
+    html
+}
+
+///
+/// The versions of `project` as a standalone html page.
+///
+#[must_use]
+pub fn html(project: &Project, versions: &[Version]) -> String {
+    let name: String = escape(project.name.as_str());
+    let mut page: String = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{name} changelog</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n<header>\n<h1>{name} changelog</h1>\n<p>{}</p>\n</header>\n<main>\n",
+        escape(project.description.as_str())
+    );
+    for v in versions {
+        page.push_str(
+            format!(
+                "<section id=\"{}\">\n<h2>{}<time datetime=\"{}\">{}</time></h2>\n{}</section>\n",
+                escape(v.tag.as_deref().unwrap_or(v.name.as_str())),
+                escape(v.name.as_str()),
+                v.date.to_rfc3339(),
+                v.date.date_naive(),
+                version_html(v)
+            )
+            .as_str(),
+        );
+    }
+    if versions.is_empty() {
+        page.push_str("<p>No changes.</p>\n");
+    }
+    page.push_str("</main>\n</body>\n</html>\n");
+    page
+}
+
+///
+/// The versions of `project` as an Atom feed, with one entry per version.
+///
+/// The entries link to the release page of their tag when the project has a releases link.
+///
+#[must_use]
+pub fn atom(project: &Project, versions: &[Version]) -> String {
+    let base: String =
+        link(project, "repository").map_or_else(|| format!("urn:{}", project.name), String::from);
+    let mut feed: Feed = Feed::default();
+    feed.set_title(format!("{} releases", project.name));
+    feed.set_id(base.as_str());
+    if let Some(v) = versions.first() {
+        feed.set_updated(v.date);
+    }
+    feed.set_authors(
+        project
+            .authors
+            .iter()
+            .map(|a| {
+                let mut p: Person = Person::default();
+                p.set_name(a.as_str());
+                p
+            })
+            .collect::<Vec<Person>>(),
+    );
+    if let Some(releases) = link(project, "releases") {
+        let mut l: Link = Link::default();
+        l.set_href(releases);
+        l.set_rel("alternate");
+        feed.set_links(vec![l]);
+    }
+    let entries: Vec<Entry> = versions
+        .iter()
+        .map(|v| {
+            let tag: &str = v.tag.as_deref().unwrap_or(v.name.as_str());
+            let mut entry: Entry = Entry::default();
+            entry.set_title(format!("{} {}", project.name, v.name));
+            entry.set_id(format!("{base}#{tag}"));
+            entry.set_updated(v.date);
+            entry.set_published(Some(v.date));
+            if let (Some(releases), Some(tag)) = (link(project, "releases"), v.tag.as_deref()) {
+                let mut l: Link = Link::default();
+                if releases.ends_with("/releases") {
+                    l.set_href(format!("{releases}/tag/{tag}"));
+                } else {
+                    l.set_href(format!("{releases}/{tag}"));
+                }
+                l.set_rel("alternate");
+                entry.set_links(vec![l]);
+            }
+            let mut content: Content = Content::default();
+            content.set_content_type(Some(String::from("html")));
+            content.set_value(Some(version_html(v)));
+            entry.set_content(Some(content));
+            entry
+        })
+        .collect();
+    feed.set_entries(entries);
+    format!("{}\n", feed.to_string())
+}
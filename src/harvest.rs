@@ -0,0 +1,221 @@
+use cargo_metadata::MetadataCommand;
+use git2::Repository;
+use ignore::{DirEntry, WalkBuilder};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::fs::{self, read_to_string};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::UNIX_EPOCH;
+
+/// The identifiers harvested from the sources, in the zazen directory of the git directory.
+pub const IDENTIFIERS_FILE: &str = "identifiers.toml";
+
+static ITEM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?m)^\s*pub(?:\([^)]*\))?\s+(?:(?:async|unsafe|const|extern\s+"[^"]*")\s+)*(?:fn|struct|enum|trait|type|const|static|mod|union|macro)\s+([A-Za-z_][A-Za-z0-9_]*)"#,
+    )
+    .expect("invalid item regex")
+});
+static FIELD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*pub(?:\([^)]*\))?\s+([a-z_][A-Za-z0-9_]*)\s*:")
+        .expect("invalid field regex")
+});
+static MODULE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+([A-Za-z_][A-Za-z0-9_]*)")
+        .expect("invalid module regex")
+});
+static USE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+([^;]+);").expect("invalid use regex")
+});
+static IDENTIFIER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").expect("invalid identifier regex"));
+
+const KEYWORDS: [&str; 5] = ["self", "Self", "crate", "super", "as"];
+
+#[derive(Serialize, Deserialize, Default)]
+struct Cache {
+    fingerprint: String,
+    words: Vec<String>,
+}
+
+fn is_source(e: &DirEntry) -> bool {
+    e.file_type().is_some_and(|t| t.is_file())
+        && (e.path().extension().is_some_and(|x| x.eq("rs"))
+            || e.file_name().eq("Cargo.toml")
+            || e.file_name().eq("Cargo.lock"))
+}
+
+///
+/// The `.rs`, `Cargo.toml` and `Cargo.lock` files of `r`, the files ignored by git being skipped.
+///
+fn sources(r: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkBuilder::new(r)
+        .build()
+        .flatten()
+        .filter(is_source)
+        .map(DirEntry::into_path)
+        .collect();
+    files.sort();
+    files
+}
+
+///
+/// A digest of the path, the size and the modification time of `files`.
+///
+/// `DefaultHasher` is not stable across Rust releases, a zazen built with another toolchain only harvests the words
+/// again.
+///
+fn fingerprint(files: &[PathBuf]) -> String {
+    let mut hasher: DefaultHasher = DefaultHasher::new();
+    for f in files {
+        f.hash(&mut hasher);
+        if let Ok(m) = fs::metadata(f) {
+            m.len().hash(&mut hasher);
+            m.modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_nanos())
+                .hash(&mut hasher);
+        }
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+///
+/// The public items, the public fields, the modules and the imported names of the Rust source `source`.
+///
+fn identifiers(source: &str, words: &mut BTreeSet<String>) {
+    for re in [&ITEM, &FIELD, &MODULE] {
+        words.extend(re.captures_iter(source).map(|c| c[1].to_string()));
+    }
+    for c in USE.captures_iter(source) {
+        words.extend(
+            IDENTIFIER
+                .find_iter(&c[1])
+                .map(|m| m.as_str())
+                .filter(|i| KEYWORDS.contains(i).eq(&false))
+                .map(String::from),
+        );
+    }
+}
+
+///
+/// The names of the packages and the targets of the workspace of `r` and of their dependencies.
+///
+fn crate_names(r: &str) -> Vec<String> {
+    let Ok(metadata) = MetadataCommand::new().current_dir(r).no_deps().exec() else {
+        return Vec::new();
+    };
+    metadata
+        .packages
+        .iter()
+        .flat_map(|p| {
+            std::iter::once(p.name.clone())
+                .chain(p.targets.iter().map(|t| t.name.clone()))
+                .chain(
+                    p.dependencies
+                        .iter()
+                        .flat_map(|d| [Some(d.name.clone()), d.rename.clone()])
+                        .flatten(),
+                )
+        })
+        .collect()
+}
+
+fn harvest(r: &str, files: &[PathBuf]) -> Vec<String> {
+    let mut words: BTreeSet<String> = BTreeSet::new();
+    for f in files
+        .iter()
+        .filter(|f| f.extension().is_some_and(|x| x.eq("rs")))
+    {
+        if let Some(stem) = f.file_stem() {
+            words.insert(stem.to_string_lossy().to_string());
+        }
+        if let Ok(source) = read_to_string(f) {
+            identifiers(source.as_str(), &mut words);
+        }
+    }
+    words.extend(crate_names(r));
+    words.into_iter().collect()
+}
+
+///
+/// The identifiers of the Rust sources of `r` and the crate names of its cargo metadata.
+///
+/// The words are cached in the git directory and harvested again when a source, a `Cargo.toml` or a `Cargo.lock`
+/// changes.
+///
+#[must_use]
+pub fn words(r: &str) -> Vec<String> {
+    let files: Vec<PathBuf> = sources(r);
+    let fingerprint: String = fingerprint(&files);
+    let cache: Option<PathBuf> = Repository::open(r)
+        .ok()
+        .map(|repo| repo.path().join("zazen").join(IDENTIFIERS_FILE));
+    if let Some(c) = cache
+        .as_deref()
+        .and_then(|p| read_to_string(p).ok())
+        .and_then(|c| toml::from_str::<Cache>(c.as_str()).ok())
+        .filter(|c| c.fingerprint.eq(&fingerprint))
+    {
+        return c.words;
+    }
+    let words: Vec<String> = harvest(r, &files);
+    if let Some(path) = cache.as_deref() {
+        save(
+            path,
+            &Cache {
+                fingerprint,
+                words: words.clone(),
+            },
+        );
+    }
+    words
+}
+
+fn save(path: &Path, cache: &Cache) {
+    if let (Some(dir), Ok(content)) = (path.parent(), toml::to_string(cache)) {
+        let _ = fs::create_dir_all(dir).and_then(|()| fs::write(path, content));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn harvested(source: &str) -> Vec<String> {
+        let mut words: BTreeSet<String> = BTreeSet::new();
+        identifiers(source, &mut words);
+        words.into_iter().collect()
+    }
+
+    #[test]
+    fn the_restricted_and_qualified_public_items_are_harvested() {
+        assert_eq!(
+            harvested(
+                "pub(crate) fn restricted() {}\npub async fn fetched() {}\npub(super) unsafe fn raw() {}\nfn hidden() {}\n"
+            ),
+            vec!["fetched", "raw", "restricted"]
+        );
+    }
+
+    #[test]
+    fn the_grouped_imports_and_their_renames_are_harvested() {
+        assert_eq!(
+            harvested("use alpha::{beta, gamma as delta};\n"),
+            vec!["alpha", "beta", "delta", "gamma"]
+        );
+    }
+
+    #[test]
+    fn the_path_keywords_are_not_harvested() {
+        assert_eq!(
+            harvested("use crate::config::{self, Config};\nuse super::parent as Parent;\n"),
+            vec!["Config", "Parent", "config", "parent"]
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use crate::lint::{self, Linter, Violation};
+use git2::Repository;
+use std::env::current_exe;
+use std::fs::{self, read_to_string, remove_file, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const COMMIT_MSG: &str = "commit-msg";
+const PRE_COMMIT: &str = "pre-commit";
+const POST_COMMIT: &str = "post-commit";
+const COMMIT_EDITMSG: &str = "COMMIT_EDITMSG";
+const MARKER: &str = "# Installed by zazen, remove it with zazen hook uninstall";
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+fn hooks_dir(repo: &Repository) -> PathBuf {
+    repo.config()
+        .and_then(|c| c.get_path("core.hooksPath"))
+        .map_or_else(
+            |_| repo.path().join("hooks"),
+            |p| {
+                if p.is_relative() {
+                    repo.workdir().unwrap_or_else(|| repo.path()).join(p)
+                } else {
+                    p
+                }
+            },
+        )
+}
+
+fn hook_path(r: &str) -> PathBuf {
+    let repo: Repository = Repository::open(r).expect("Not a git repository");
+    hooks_dir(&repo).join(COMMIT_MSG)
+}
+
+fn is_zazen(path: &Path) -> bool {
+    read_to_string(path).is_ok_and(|x| x.contains(MARKER))
+}
+
+///
+/// Install the commit-msg hook in the repository `r`, an existing hook not installed by zazen is kept.
+///
+/// # Panics
+///
+/// If the hook cannot be written.
+///
+#[must_use]
+pub fn install(r: &str) -> bool {
+    let path: PathBuf = hook_path(r);
+    if path.exists() && is_zazen(&path).eq(&false) {
+        eprintln!(
+            "{} already exists and was not installed by zazen",
+            path.display()
+        );
+        return false;
+    }
+    let zazen: String =
+        current_exe().map_or_else(|_| String::from("zazen"), |p| p.display().to_string());
+    fs::create_dir_all(path.parent().expect("no hooks directory"))
+        .expect("failed to create the hooks directory");
+    let mut f: File = File::create(&path).expect("failed to create the hook");
+    writeln!(
+        f,
+        "#!/bin/sh\n{MARKER}\nexec \"{zazen}\" hook {COMMIT_MSG} \"$1\""
+    )
+    .expect("failed to write the hook");
+    make_executable(&path);
+    println!("{} installed", path.display());
+    true
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .expect("failed to make the hook executable");
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) {}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+///
+/// Run the hook `name` of `repo` with `args` like git does, in the working tree and with the index of `repo`, a
+/// missing or not executable hook being skipped.
+///
+/// # Errors
+///
+/// If the hook cannot be run or fails.
+///
+pub fn run(repo: &Repository, name: &str, args: &[&str]) -> Result<(), String> {
+    let path: PathBuf = hooks_dir(repo).join(name);
+    if is_executable(&path).eq(&false) {
+        return Ok(());
+    }
+    let status = Command::new(&path)
+        .args(args)
+        .current_dir(repo.workdir().unwrap_or_else(|| repo.path()))
+        .env("GIT_INDEX_FILE", repo.path().join("index"))
+        .status()
+        .map_err(|e| format!("failed to run the {name} hook : {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("the {name} hook failed"))
+    }
+}
+
+///
+/// Run the pre-commit hook of `repo`.
+///
+/// # Errors
+///
+/// If the hook cannot be run or fails.
+///
+pub fn pre_commit(repo: &Repository) -> Result<(), String> {
+    run(repo, PRE_COMMIT, &[])
+}
+
+///
+/// Run the commit-msg hook of `repo` on `message`, written in `.git/COMMIT_EDITMSG` like git does, the message
+/// edited by the hook being returned.
+///
+/// # Errors
+///
+/// If the message cannot be written or read, or the hook fails.
+///
+pub fn commit_message(repo: &Repository, message: &str) -> Result<String, String> {
+    let path: PathBuf = repo.path().join(COMMIT_EDITMSG);
+    fs::write(&path, message).map_err(|e| format!("failed to write {} : {e}", path.display()))?;
+    run(repo, COMMIT_MSG, &[path.to_string_lossy().as_ref()])?;
+    read_to_string(&path).map_err(|e| format!("failed to read {} : {e}", path.display()))
+}
+
+///
+/// Run the post-commit hook of `repo`, its failure being only reported as git does.
+///
+pub fn post_commit(repo: &Repository) {
+    if let Err(e) = run(repo, POST_COMMIT, &[]) {
+        eprintln!("{e}");
+    }
+}
+
+///
+/// Remove the commit-msg hook installed by zazen.
+///
+#[must_use]
+pub fn uninstall(r: &str) -> bool {
+    let path: PathBuf = hook_path(r);
+    if path.exists().eq(&false) {
+        println!("No {COMMIT_MSG} hook installed");
+        return true;
+    }
+    if is_zazen(&path).eq(&false) {
+        eprintln!("{} was not installed by zazen", path.display());
+        return false;
+    }
+    remove_file(&path).is_ok()
+}
+
+///
+/// Display if the commit-msg hook is installed.
+///
+#[must_use]
+pub fn status(r: &str) -> bool {
+    let path: PathBuf = hook_path(r);
+    if path.exists().eq(&false) {
+        println!("{COMMIT_MSG} : not installed");
+    } else if is_zazen(&path) {
+        println!("{COMMIT_MSG} : installed in {}", path.display());
+    } else {
+        println!(
+            "{COMMIT_MSG} : {} was not installed by zazen",
+            path.display()
+        );
+    }
+    true
+}
+
+///
+/// Check the commit message stored in `file` with the lint rules, the messages generated by git are accepted.
+///
+#[must_use]
+pub fn commit_msg(r: &str, file: &str) -> bool {
+    let mut text: String = match read_to_string(file) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("zazen: failed to read the commit message {file} : {e}");
+            return false;
+        }
+    };
+    if let Some(x) = text.find(SCISSORS) {
+        text.truncate(x);
+    }
+    if lint::generated(text.as_str()) {
+        return true;
+    }
+    let violations: Vec<Violation> = match Linter::new(r) {
+        Ok(linter) => linter.lint(text.as_str()),
+        Err(e) => {
+            eprintln!("zazen: {e}");
+            return false;
+        }
+    };
+    if violations.is_empty() {
+        return true;
+    }
+    let errors: bool = lint::has_errors(&violations);
+    if errors {
+        eprintln!("zazen: the commit message breaks the lint rules\n");
+    } else {
+        eprintln!("zazen: the commit message has warnings\n");
+    }
+    for v in &violations {
+        eprintln!("  - {v}");
+    }
+    if errors {
+        eprintln!("\nYour message has been kept in {file}");
+    }
+    errors.eq(&false)
+}
@@ -0,0 +1,710 @@
+use crate::config::{self, Config};
+use crate::conventional::{Style, Translator};
+use crate::message::{CommitMessage, SCOPE_LENGTH, SUMMARY_LENGTH, WHY_LENGTH};
+use crate::spelling::Checker;
+use crate::template::config_dir;
+use git2::Repository;
+use globset::Glob;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::MAIN_SEPARATOR_STR;
+use std::sync::LazyLock;
+use toml::Table;
+
+const RULES: &str = include_str!("lint.toml");
+
+/// The lint rules, in the zazen directory of the repository or of the user configuration.
+pub const LINT_FILE: &str = "lint.toml";
+
+/// The subject prefixes of the messages written by git, never linted.
+const GENERATED: [&str; 5] = ["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "];
+
+const HEADER: &str = "header";
+const COMMIT_TYPE: &str = "commit-type";
+const SCOPE: &str = "scope-length";
+const SUBJECT: &str = "subject-length";
+const WHY: &str = "why-length";
+const TRAILING_PERIOD: &str = "trailing-period";
+const IMPERATIVE_MOOD: &str = "imperative-mood";
+const BODY_WRAP: &str = "body-wrap";
+const ISSUE_REFERENCE: &str = "issue-reference";
+const FORBIDDEN_TYPES: &str = "forbidden-types";
+const SPELLING: &str = "spelling";
+
+const NAMES: [&str; 11] = [
+    HEADER,
+    COMMIT_TYPE,
+    SCOPE,
+    SUBJECT,
+    WHY,
+    TRAILING_PERIOD,
+    IMPERATIVE_MOOD,
+    BODY_WRAP,
+    ISSUE_REFERENCE,
+    FORBIDDEN_TYPES,
+    SPELLING,
+];
+
+const BODY_WIDTH: usize = 72;
+const NOT_PARTICIPLES: [&str; 7] = [
+    "embed", "speed", "shred", "bring", "string", "spring", "thing",
+];
+const NOT_THIRD_PERSON: [&str; 4] = ["ss", "us", "is", "as"];
+
+static ISSUE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:#\d+|\b[A-Z][A-Z0-9]+-\d+\b)").expect("invalid issue regex"));
+
+///
+/// What a broken rule does to the message.
+///
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+    Off,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Off => write!(f, "off"),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Default)]
+struct Rule {
+    #[serde(default)]
+    severity: Severity,
+    #[serde(default)]
+    max: Option<usize>,
+    #[serde(default)]
+    branches: Vec<String>,
+    #[serde(default)]
+    types: Vec<String>,
+}
+
+///
+/// A rule broken by a commit message.
+///
+#[derive(Clone, Debug)]
+pub struct Violation {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}] : {}", self.severity, self.rule, self.message)
+    }
+}
+
+///
+/// Check if `violations` contains an error.
+///
+#[must_use]
+pub fn has_errors(violations: &[Violation]) -> bool {
+    violations.iter().any(|v| v.severity.eq(&Severity::Error))
+}
+
+///
+/// Check if `text` was written by git, a merge, a revert or an autosquash message.
+///
+#[must_use]
+pub fn generated(text: &str) -> bool {
+    GENERATED.iter().any(|g| text.starts_with(g))
+}
+
+///
+/// The rules of the file `path`, empty when it does not exist.
+///
+/// # Errors
+///
+/// If the file cannot be read or parsed, or names an unknown rule.
+///
+fn read(path: &str) -> Result<Table, String> {
+    let table: Table = config::read(path)?;
+    if let Some(unknown) = table
+        .keys()
+        .find(|k| NAMES.contains(&k.as_str()).eq(&false))
+    {
+        return Err(format!(
+            "{path} : {unknown} is not a lint rule, use one of {}",
+            NAMES.join(", ")
+        ));
+    }
+    Ok(table)
+}
+
+///
+/// The rules of the repository `r`, `zazen/lint.toml` merged over the rules of the user and the builtin ones.
+///
+/// # Errors
+///
+/// If a rules file cannot be read or parsed, names an unknown rule or has invalid options.
+///
+fn load(r: &str) -> Result<BTreeMap<String, Rule>, String> {
+    let mut table: Table = RULES.parse().expect("invalid builtin lint rules");
+    if let Some(d) = config_dir() {
+        config::merge(
+            &mut table,
+            read(format!("{d}{MAIN_SEPARATOR_STR}{LINT_FILE}").as_str())?,
+        );
+    }
+    config::merge(
+        &mut table,
+        read(format!("{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}{LINT_FILE}").as_str())?,
+    );
+    table
+        .try_into()
+        .map_err(|e| format!("the lint rules are invalid : {e}"))
+}
+
+fn matches(globs: &[String], branch: &str) -> bool {
+    globs.iter().any(|g| {
+        Glob::new(g)
+            .map(|g| g.compile_matcher().is_match(branch))
+            .unwrap_or(false)
+    })
+}
+
+///
+/// Check if the first word of `summary` looks like an imperative verb, `added`, `adding` or `adds` do not.
+///
+fn imperative(summary: &str) -> bool {
+    let word: String = summary
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let length: usize = word.chars().count();
+    if word.chars().all(|c| c.is_ascii_alphabetic()).eq(&false) || length.lt(&4) {
+        return true;
+    }
+    if NOT_PARTICIPLES.contains(&word.as_str()) {
+        return true;
+    }
+    if length.gt(&4) && (word.ends_with("ed") || word.ends_with("ing")) {
+        return false;
+    }
+    word.ends_with('s').eq(&false) || NOT_THIRD_PERSON.iter().any(|e| word.ends_with(e))
+}
+
+///
+/// The rules of a repository, with the branch and the commit types they depend on.
+///
+pub struct Linter {
+    rules: BTreeMap<String, Rule>,
+    branch: Option<String>,
+    translator: Translator,
+    optional_scope: bool,
+    root: String,
+    languages: Vec<String>,
+    checker: Option<Checker>,
+}
+
+impl Linter {
+    ///
+    /// The rules of the repository `r`, the branch rules using its current branch.
+    ///
+    /// # Errors
+    ///
+    /// If a rules, configuration or commit types file cannot be read or parsed, or a rules file names an unknown rule
+    /// or has invalid options.
+    ///
+    pub fn new(r: &str) -> Result<Self, String> {
+        let rules: BTreeMap<String, Rule> = load(r)?;
+        let branch: Option<String> = Repository::open(r).ok().and_then(|repo| {
+            repo.head()
+                .ok()
+                .filter(git2::Reference::is_branch)
+                .and_then(|h| h.shorthand().map(String::from))
+        });
+        let config: Config = config::load(r)?;
+        let checker: Option<Checker> = rules
+            .get(SPELLING)
+            .is_some_and(|s| s.severity.ne(&Severity::Off))
+            .then(|| Checker::new(r, &config.spelling.languages));
+        Ok(Self {
+            rules,
+            branch,
+            translator: Translator::new(r)?,
+            optional_scope: config.commit.style.eq(&Style::Conventional),
+            root: r.to_string(),
+            languages: config.spelling.languages,
+            checker,
+        })
+    }
+
+    ///
+    /// The spelling checker of the rules, shared with the prompts and built on first use when the spelling rule is
+    /// off. The words it learns are accepted by the next checks of the rules.
+    ///
+    pub fn checker(&mut self) -> &mut Checker {
+        self.checker
+            .get_or_insert_with(|| Checker::new(self.root.as_str(), &self.languages))
+    }
+
+    fn rule(&self, name: &str) -> Rule {
+        self.rules.get(name).cloned().unwrap_or_default()
+    }
+
+    ///
+    /// The maximum length of the scope, the summary or a reason enforced at the prompt, unlimited when its rule is
+    /// not an error.
+    ///
+    fn limit(&self, name: &str, default: usize) -> usize {
+        let rule: Rule = self.rule(name);
+        if rule.severity.eq(&Severity::Error) {
+            rule.max.unwrap_or(default)
+        } else {
+            usize::MAX
+        }
+    }
+
+    ///
+    /// The maximum length of a scope.
+    ///
+    #[must_use]
+    pub fn scope_length(&self) -> usize {
+        self.limit(SCOPE, SCOPE_LENGTH)
+    }
+
+    ///
+    /// The maximum length of a summary.
+    ///
+    #[must_use]
+    pub fn summary_length(&self) -> usize {
+        self.limit(SUBJECT, SUMMARY_LENGTH)
+    }
+
+    ///
+    /// The maximum length of a reason.
+    ///
+    #[must_use]
+    pub fn why_length(&self) -> usize {
+        self.limit(WHY, WHY_LENGTH)
+    }
+
+    fn report(&self, violations: &mut Vec<Violation>, rule: &'static str, message: String) {
+        let severity: Severity = self.rule(rule).severity;
+        if severity.ne(&Severity::Off) {
+            violations.push(Violation {
+                rule,
+                severity,
+                message,
+            });
+        }
+    }
+
+    fn same_type(&self, a: &str, b: &str) -> bool {
+        let a: String = self.translator.cosmic(a).unwrap_or_else(|| a.to_string());
+        let b: String = self.translator.cosmic(b).unwrap_or_else(|| b.to_string());
+        a.trim().eq_ignore_ascii_case(b.trim())
+    }
+
+    ///
+    /// The rules broken by the commit message `text`, the lines starting with `#` being ignored.
+    ///
+    #[must_use]
+    pub fn lint(&self, text: &str) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = Vec::new();
+        let Some(m) = CommitMessage::parse(text) else {
+            self.report(
+                &mut violations,
+                HEADER,
+                String::from("the first line must look like type(scope): summary"),
+            );
+            return violations;
+        };
+        if self.translator.knows(m.commit_type.as_str()).eq(&false) {
+            self.report(
+                &mut violations,
+                COMMIT_TYPE,
+                format!("{} is not a commit type", m.commit_type),
+            );
+        }
+        self.lengths(&m, &mut violations);
+        if m.summary.trim_end().ends_with('.') {
+            self.report(
+                &mut violations,
+                TRAILING_PERIOD,
+                String::from("the summary must not end with a period"),
+            );
+        }
+        if imperative(m.summary.as_str()).eq(&false) {
+            self.report(
+                &mut violations,
+                IMPERATIVE_MOOD,
+                format!(
+                    "the summary must start with an imperative verb, found {}",
+                    m.summary.split_whitespace().next().unwrap_or_default()
+                ),
+            );
+        }
+        self.body(text, &mut violations);
+        self.branch_rules(text, &m, &mut violations);
+        self.spelling(&m, &mut violations);
+        violations
+    }
+
+    fn lengths(&self, m: &CommitMessage, violations: &mut Vec<Violation>) {
+        let scope: usize = self.rule(SCOPE).max.unwrap_or(SCOPE_LENGTH);
+        let length: usize = m.scope.chars().count();
+        if (length.eq(&0) && self.optional_scope.eq(&false)) || length.gt(&scope) {
+            self.report(
+                violations,
+                SCOPE,
+                format!("the scope must contains between 1 and {scope} characters, found {length}"),
+            );
+        }
+        let summary: usize = self.rule(SUBJECT).max.unwrap_or(SUMMARY_LENGTH);
+        let length: usize = m.summary.chars().count();
+        if length.eq(&0) || length.gt(&summary) {
+            self.report(
+                violations,
+                SUBJECT,
+                format!(
+                    "the summary must contains between 1 and {summary} characters, found {length}"
+                ),
+            );
+        }
+        let why: usize = self.rule(WHY).max.unwrap_or(WHY_LENGTH);
+        for w in &m.why {
+            let length: usize = w.chars().count();
+            if length.gt(&why) {
+                self.report(
+                    violations,
+                    WHY,
+                    format!(
+                        "a reason must contains less than {why} characters, found {length} in {w}"
+                    ),
+                );
+            }
+        }
+    }
+
+    ///
+    /// The body lines longer than the wrap width, the lines without spaces like the links being accepted.
+    ///
+    fn body(&self, text: &str, violations: &mut Vec<Violation>) {
+        let width: usize = self.rule(BODY_WRAP).max.unwrap_or(BODY_WIDTH);
+        for (n, line) in text
+            .lines()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, l)| l.starts_with('#').eq(&false))
+            .filter(|(_, l)| l.trim().contains(char::is_whitespace))
+        {
+            let length: usize = line.chars().count();
+            if length.gt(&width) {
+                self.report(
+                    violations,
+                    BODY_WRAP,
+                    format!(
+                        "the line {} must be wrapped at {width} characters, found {length}",
+                        n + 1
+                    ),
+                );
+            }
+        }
+    }
+
+    fn branch_rules(&self, text: &str, m: &CommitMessage, violations: &mut Vec<Violation>) {
+        let Some(branch) = self.branch.as_deref() else {
+            return;
+        };
+        let reference: Rule = self.rule(ISSUE_REFERENCE);
+        if matches(&reference.branches, branch)
+            && m.fixes.is_empty()
+            && m.closes.is_empty()
+            && ISSUE.is_match(text).eq(&false)
+        {
+            self.report(
+                violations,
+                ISSUE_REFERENCE,
+                format!("the commits of {branch} must reference an issue"),
+            );
+        }
+        let forbidden: Rule = self.rule(FORBIDDEN_TYPES);
+        if matches(&forbidden.branches, branch)
+            && forbidden
+                .types
+                .iter()
+                .any(|t| self.same_type(t, m.commit_type.as_str()))
+        {
+            self.report(
+                violations,
+                FORBIDDEN_TYPES,
+                format!("{} commits are forbidden on {branch}", m.commit_type),
+            );
+        }
+    }
+
+    fn spelling(&self, m: &CommitMessage, violations: &mut Vec<Violation>) {
+        if self.rule(SPELLING).severity.eq(&Severity::Off) {
+            return;
+        }
+        let Some(checker) = self.checker.as_ref() else {
+            return;
+        };
+        if let Some(missing) = checker.missing() {
+            violations.push(Violation {
+                rule: SPELLING,
+                severity: Severity::Warning,
+                message: missing,
+            });
+        }
+        let mut words: Vec<String> = checker
+            .misspelled(
+                [
+                    m.scope.as_str(),
+                    m.summary.as_str(),
+                    m.why.join("\n").as_str(),
+                    m.footer_prose().join("\n").as_str(),
+                ]
+                .join("\n")
+                .as_str(),
+            )
+            .into_iter()
+            .map(|w| w.word)
+            .collect();
+        words.sort();
+        words.dedup();
+        if words.is_empty().eq(&false) {
+            self.report(
+                violations,
+                SPELLING,
+                format!("unknown words {}", words.join(", ")),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Oid, Signature};
+    use std::fs;
+    use tempfile::TempDir;
+
+    ///
+    /// A repository on `branch` with the lint rules `rules` and the spelling checked in a missing language.
+    ///
+    fn repository(branch: &str, rules: &str) -> TempDir {
+        let dir: TempDir = tempfile::tempdir().expect("temporary directory");
+        let repo: Repository = Repository::init(dir.path()).expect("repository");
+        let zazen = dir.path().join("zazen");
+        fs::create_dir_all(&zazen).expect("zazen directory");
+        fs::write(zazen.join(LINT_FILE), rules).expect("lint rules");
+        fs::write(
+            zazen.join(config::CONFIG_FILE),
+            "[spelling]\nlanguages = [\"zz_ZZ\"]\n",
+        )
+        .expect("configuration");
+        let signature: Signature<'_> = Signature::now("Ann", "ann@example.com").expect("signature");
+        let tree: Oid = repo.index().and_then(|mut i| i.write_tree()).expect("tree");
+        let tree = repo.find_tree(tree).expect("tree");
+        repo.commit(
+            Some(format!("refs/heads/{branch}").as_str()),
+            &signature,
+            &signature,
+            "Star(repo): start",
+            &tree,
+            &[],
+        )
+        .expect("commit");
+        repo.set_head(format!("refs/heads/{branch}").as_str())
+            .expect("head");
+        dir
+    }
+
+    fn linter_of(dir: &TempDir) -> Linter {
+        Linter::new(dir.path().to_str().expect("utf-8 path")).expect("valid rules")
+    }
+
+    fn broken(linter: &Linter, text: &str) -> Vec<(&'static str, Severity)> {
+        linter
+            .lint(text)
+            .into_iter()
+            .filter(|v| v.rule.ne(SPELLING))
+            .map(|v| (v.rule, v.severity))
+            .collect()
+    }
+
+    #[test]
+    fn a_valid_message_breaks_no_rule() {
+        let dir: TempDir = repository("main", "");
+        let linter: Linter = linter_of(&dir);
+        assert!(broken(
+            &linter,
+            "Star(lint): add the rules\n\n* check the history\n"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn the_header_and_the_type_are_checked() {
+        let dir: TempDir = repository("main", "");
+        let linter: Linter = linter_of(&dir);
+        assert_eq!(
+            broken(&linter, "add the rules"),
+            vec![(HEADER, Severity::Error)]
+        );
+        assert_eq!(
+            broken(&linter, "Nothing(lint): add the rules"),
+            vec![(COMMIT_TYPE, Severity::Error)]
+        );
+    }
+
+    #[test]
+    fn lengths_are_counted_in_characters() {
+        let dir: TempDir = repository("main", "[scope-length]\nmax = 3\n");
+        let linter: Linter = linter_of(&dir);
+        assert!(broken(&linter, "Star(été): add the rules").is_empty());
+        assert_eq!(
+            broken(&linter, "Star(étés): add the rules"),
+            vec![(SCOPE, Severity::Error)]
+        );
+        let summary: String = format!("add {}", "é".repeat(46));
+        assert!(broken(&linter, format!("Star(été): {summary}").as_str()).is_empty());
+        assert_eq!(
+            broken(&linter, format!("Star(été): {summary}é").as_str()),
+            vec![(SUBJECT, Severity::Error)]
+        );
+        let why: String = "é".repeat(50);
+        assert!(broken(&linter, format!("Star(été): add it\n\n* {why}\n").as_str()).is_empty());
+        assert_eq!(
+            broken(&linter, format!("Star(été): add it\n\n* {why}é\n").as_str()),
+            vec![(WHY, Severity::Error)]
+        );
+    }
+
+    #[test]
+    fn the_summary_style_rules_are_warnings() {
+        let dir: TempDir = repository("main", "");
+        let linter: Linter = linter_of(&dir);
+        assert_eq!(
+            broken(&linter, "Star(lint): add the rules."),
+            vec![(TRAILING_PERIOD, Severity::Warning)]
+        );
+        assert_eq!(
+            broken(&linter, "Star(lint): added the rules"),
+            vec![(IMPERATIVE_MOOD, Severity::Warning)]
+        );
+        assert_eq!(
+            broken(&linter, "Star(lint): adds the rules"),
+            vec![(IMPERATIVE_MOOD, Severity::Warning)]
+        );
+        assert!(broken(&linter, "Star(lint): address the rules").is_empty());
+        assert!(broken(&linter, "Star(lint): embed the rules").is_empty());
+    }
+
+    #[test]
+    fn the_body_is_wrapped_except_the_links() {
+        let dir: TempDir = repository("main", "[body-wrap]\nmax = 20\n");
+        let linter: Linter = linter_of(&dir);
+        assert_eq!(
+            broken(
+                &linter,
+                "Star(lint): add it\n\nthis line is longer than twenty\n"
+            ),
+            vec![(BODY_WRAP, Severity::Warning)]
+        );
+        assert!(broken(
+            &linter,
+            "Star(lint): add it\n\nhttps://example.com/a/very/long/link\n# a comment longer than twenty\n"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn the_branch_rules_use_the_current_branch() {
+        let rules: &str = "[forbidden-types]\nbranches = [\"hotfix/*\"]\ntypes = [\"Star\"]\n";
+        let dir: TempDir = repository("hotfix/crash", rules);
+        let linter: Linter = linter_of(&dir);
+        assert_eq!(
+            broken(&linter, "Comet(lint): fix the crash"),
+            vec![(ISSUE_REFERENCE, Severity::Error)]
+        );
+        assert!(broken(&linter, "Comet(lint): fix the crash\n\nFixes #3\n").is_empty());
+        assert!(broken(&linter, "Comet(lint): fix the crash of ABC-12").is_empty());
+        assert_eq!(
+            broken(&linter, "Star(lint): add a feature\n\nFixes #3\n"),
+            vec![(FORBIDDEN_TYPES, Severity::Error)]
+        );
+        let main: TempDir = repository("main", rules);
+        assert!(broken(&linter_of(&main), "Star(lint): add a feature").is_empty());
+    }
+
+    #[test]
+    fn a_rule_can_be_turned_off() {
+        let dir: TempDir = repository("main", "[trailing-period]\nseverity = \"off\"\n");
+        assert!(broken(&linter_of(&dir), "Star(lint): add the rules.").is_empty());
+    }
+
+    #[test]
+    fn a_missing_dictionary_is_a_warning() {
+        let dir: TempDir = repository("main", "");
+        let violations: Vec<Violation> = linter_of(&dir).lint("Star(lint): add the rules");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, SPELLING);
+        assert_eq!(violations[0].severity, Severity::Warning);
+        assert!(has_errors(&violations).eq(&false));
+    }
+
+    #[test]
+    fn the_words_learned_at_the_prompts_are_accepted() {
+        let dir: TempDir = repository("main", "");
+        let zazen = dir.path().join("zazen");
+        fs::write(
+            zazen.join(config::CONFIG_FILE),
+            "[spelling]\nlanguages = [\"xx_XX\"]\n",
+        )
+        .expect("configuration");
+        fs::create_dir_all(zazen.join("dictionaries")).expect("dictionaries directory");
+        fs::write(zazen.join("dictionaries").join("xx_XX.aff"), "SET UTF-8\n").expect("aff");
+        fs::write(
+            zazen.join("dictionaries").join("xx_XX.dic"),
+            "4\nlint\nadd\nthe\nrules\n",
+        )
+        .expect("dic");
+        let mut linter: Linter = linter_of(&dir);
+        let text: &str = "Star(lint): add the zazen rules";
+        assert_eq!(
+            linter
+                .lint(text)
+                .into_iter()
+                .map(|v| (v.rule, v.message))
+                .collect::<Vec<(&str, String)>>(),
+            vec![(SPELLING, String::from("unknown words zazen"))]
+        );
+        linter.checker().add(&[String::from("zazen")]);
+        assert!(linter.lint(text).is_empty());
+    }
+
+    #[test]
+    fn unknown_rules_and_invalid_options_are_errors() {
+        let dir: TempDir = repository("main", "[trailing-dot]\nseverity = \"warning\"\n");
+        let error: String = Linter::new(dir.path().to_str().expect("utf-8 path"))
+            .err()
+            .expect("unknown rule");
+        assert!(error.contains("trailing-dot is not a lint rule"));
+        let dir: TempDir = repository("main", "[body-wrap]\nseverity = \"loud\"\n");
+        assert!(Linter::new(dir.path().to_str().expect("utf-8 path")).is_err());
+    }
+
+    #[test]
+    fn the_messages_of_git_are_generated() {
+        assert!(generated("Merge branch 'main'"));
+        assert!(generated("fixup! Star(lint): add the rules"));
+        assert!(generated("Star(lint): add the rules").eq(&false));
+    }
+}
@@ -1,32 +1,74 @@
 #![allow(clippy::multiple_crate_versions)]
 
-use cargo_metadata::MetadataCommand;
+mod changelog;
+mod classifier;
+mod cli;
+mod commit_types;
+mod config;
+mod conventional;
+mod draft;
+mod export;
+mod harvest;
+mod hook;
+mod lint;
+mod message;
+mod rewrite;
+mod scope;
+mod signing;
+mod spelling;
+mod staging;
+mod template;
+mod trailers;
+mod workspace;
+
+use changelog::{Format, Project, Release, CHANGELOG_MD};
 use chrono::Utc;
+use clap::Parser;
+use classifier::Recommendation;
+use cli::{Action, AmendArgs, Cli, CommitArgs, FixupArgs, HookAction, ReadmeAction, RefAction};
 use colored::Colorize;
+use commit_types::CommitType;
+use config::{Config, SpellingConfig};
+use conventional::{Style, Translator};
+use draft::{Draft, Step};
 use git2::{
     BranchType, Branches, Commit, Diff, DiffFormat, DiffOptions, DiffStats, Index, Repository,
-    Revwalk, Status, StatusOptions, Statuses,
+    Revwalk,
 };
-use indicatif::{ProgressBar, ProgressStyle};
-use inquire::{Confirm, MultiSelect, Select, Text};
+use inquire::{Confirm, InquireError, MultiSelect, Select, Text};
+use lint::{Linter, Violation};
+use message::CommitMessage;
+use scope::ScopeCompleter;
+use spelling::{Checker, Misspelling, DICTIONARY_FILE};
+use staging::{Entry, Kind};
+use std::collections::HashMap;
 use std::env::consts::OS;
 use std::env::set_current_dir;
 use std::fs::{self, read_to_string, remove_file, File};
-use std::io::Write;
+use std::io::{stdout, IsTerminal};
 use std::path::Path;
 use std::path::MAIN_SEPARATOR_STR;
-use std::process::{Command, Stdio};
-use std::thread::sleep;
-use std::time::Duration;
+use std::process::{Command, ExitCode, Stdio};
+use template::{Template, Value};
 use walkdir::WalkDir;
-const COMMIT_TEMPLATE: &str = "%type%(%scope%): %summary%\n\n\tThe following changes were made :\n\n%why%\n\n%footer%\n\n\tAuthored by :\n\n\t\t* %author% <%email%> the %date%\n";
+use workspace::Member;
+const COMMIT_TEMPLATE: &str = include_str!("commit-template");
+const COMMIT_TEMPLATE_FILE: &str = "commit-template";
+const CONVENTIONAL_TEMPLATE: &str = include_str!("conventional-template");
+const CONVENTIONAL_TEMPLATE_FILE: &str = "conventional-template";
+const ANNOUNCEMENT_TEMPLATE: &str = include_str!("announcement-template");
+const ANNOUNCEMENT_TEMPLATE_FILE: &str = "announcement-template";
 const CRATES_PATH: &str = "CRATES_PATH";
 const CRATES_EDITOR: &str = "CRATES_EDITOR";
+const EDITOR: &str = "EDITOR";
 const INIT: &str = "Init flow";
 const CHECK_README_WORDS: &str = "Check readme words";
 const DISPLAY_README: &str = "Display readme";
 const GENERATE_README: &str = "Generate the README.md";
 const COMMIT: &str = "Add a commit";
+const AMEND: &str = "Amend the last commit";
+const FIXUP: &str = "Fixup a commit";
+const AUTOSQUASH: &str = "Autosquash the unpushed commits";
 const CHANGE_OF_PROJECT: &str = "Change of project";
 const STASH: &str = "Stash modification";
 const QUIT: &str = "Quit";
@@ -108,9 +150,12 @@ const README_FILES: [&str; 7] = [
     "see.md",
 ];
 
-const OPTIONS: [&str; 74] = [
+const OPTIONS: [&str; 77] = [
     INIT,
     COMMIT,
+    AMEND,
+    FIXUP,
+    AUTOSQUASH,
     CHANGE_OF_PROJECT,
     GENERATE_README,
     OPEN_THE_PROJECT,
@@ -185,114 +230,69 @@ const OPTIONS: [&str; 74] = [
     GENERATE_CHANGE_LOG,
 ];
 
-const CHECK_FILE: &str = "zen";
-
-const LANG: &str = "en_US";
-
-const COMMITS_TYPES: [&str; 68] = [
-    "Star: New feature or enhancement",
-    "Comet: Bug fix or error resolution",
-    "Nebula: Code refactoring",
-    "Pulsar: Performance improvement",
-    "Quasar: Documentation or clarity improvement",
-    "Asteroid Belt: Code cleanup and maintenance",
-    "Solar Flare: Testing-related changes",
-    "Dwarf Planet: Minor updates or fixes",
-    "Terraform: Infrastructure changes",
-    "Black Hole: Removing large chunks of code or features",
-    "Wormhole: Merging branches or connecting code parts",
-    "Big Bang: Initial commit or major feature start",
-    "Launch: Deploying to production or releasing a version",
-    "Lightspeed: Significant performance improvements",
-    "Mission Control: Project management changes",
-    "Spacewalk: Urgent hotfixes",
-    "Moon Landing: Major milestone or goal completion",
-    "First Contact: Initial integrations with external systems",
-    "Interstellar Communication: Improving documentation or communication",
-    "Solar Eclipse: Temporarily masking functionality",
-    "Supernova: Major, transformative change",
-    "Meteor Shower: Series of small changes or fixes",
-    "Solar Wind: Refactoring code structure",
-    "Lunar Eclipse: Temporarily disabling a feature",
-    "Cosmic Dawn: Initial implementation of a feature",
-    "Solar Storm: Rapid, impactful changes",
-    "Lunar Transit: Minor, temporary change",
-    "Perihelion: Brings the project closer to its goals or objectives",
-    "Aphelion: Immediate goals, but is necessary for long-term progress",
-    "White Dwarf: Improving code comments or documentation",
-    "Red Giant: Expanding a feature or functionality",
-    "Neutron Star: Optimizing code for performance",
-    "Binary Star: Merging features or components",
-    "Brown Dwarf: Undeveloped feature with potential",
-    "Quark Star: Experimental or speculative change",
-    "Rogue Planet: Independent change",
-    "Stellar Nursery: Creation of new components",
-    "Planetary Nebula: Removal or deprecation of a component",
-    "Globular Cluster: Collection of related changes",
-    "Void: Removal of a module, component, or feature",
-    "Gravity: Resolving merge conflicts or dependencies",
-    "Dark Matter: Fixing unknown or mysterious bugs",
-    "Time Dilation: Improving code performance",
-    "Spacetime: Changes to date, time, or scheduling",
-    "Gravitational Lensing: Altering data or information flow",
-    "Cosmic String: Connecting code parts",
-    "Quantum Fluctuation: Small, random change",
-    "Hawking Radiation: Removing technical debt",
-    "Quantum Entanglement: Establishing close relationships between code parts",
-    "Gravitational Redshift: Slowing down or reducing code performance",
-    "Space Probe: Testing new features or technologies",
-    "Station: Creating or improving environments",
-    "Rocket Launch: Deploying to production",
-    "Spacewalk: Urgent production hotfixes",
-    "Space Elevator: Making codebase more accessible",
-    "Warp Drive: Significant speed improvement",
-    "Dyson Sphere: Comprehensive optimization of a specific area",
-    "Generation Ship: Long-term project for a self -sustaining system",
-    "Lagrange Point: Stabilizing or balancing code parts",
-    "Orbital Maneuver: Changing project direction",
-    "Mission Control: Represents project management-related changes",
-    "Moon Landing: Celebrates the completion of major milestones",
-    "Interstellar Travel: Migration to a new architecture or language",
-    "Rover: Exploration of new technologies or approaches",
-    "Singularity: Resolution of a complex or hard-to-reproduce issue",
-    "Relativity: Changes related to time, dates, or timestamps",
-    "Expansion: Scaling up the system or increasing capacity",
-    "Big Crunch: Reduction of codebase size or removal of features",
-];
+const REVIEW_HELP: &str = "
+# Review the commit message, the lines starting with '#' are ignored.
+# The header must stay type(scope): summary, an empty message aborts the commit.
+";
 
-fn check(sentence: &str, path: &str) -> bool {
-    let mut f: File = File::create(path).expect("msg");
-    writeln!(f, "{sentence}").expect("msg");
-    let o = Command::new("hunspell")
-        .arg("-d")
-        .arg(LANG)
-        .arg("-l")
-        .arg(path)
-        .output()
-        .expect("msg")
-        .stdout;
-    if o.is_empty() {
+const RESUME_DRAFT: &str = "Resume the draft";
+const EDIT_DRAFT: &str = "Edit the draft";
+const DISCARD_DRAFT: &str = "Discard the draft";
+
+///
+/// Check the spelling of `sentence` with `checker`, the misspelled words are displayed with their corrections and
+/// can be added to the dictionary of the project.
+///
+fn check(r: &str, checker: &mut Checker, sentence: &str, source: &str) -> bool {
+    if let Some(missing) = checker.missing() {
+        eprintln!("{}", missing.yellow());
+    }
+    let errors: Vec<Misspelling> = checker.misspelled(sentence);
+    if errors.is_empty() {
         return true;
     }
-    arrange(path)
+    println!("\n  Misspelled words in {source} :\n");
+    for e in &errors {
+        println!(
+            "    {} {}",
+            e.word.red(),
+            e.suggestions.join(", ").as_str().dimmed()
+        );
+    }
+    println!();
+    let words: Vec<String> = errors.into_iter().map(|e| e.word).collect();
+    let learned: bool = learn(r, &words);
+    if learned {
+        checker.add(&words);
+    }
+    learned
 }
 
-fn check_commit(sentence: &str) -> bool {
-    let mut f: File = File::create(CHECK_FILE).expect("msg");
-    writeln!(f, "{sentence}").expect("msg");
-    let o = Command::new("hunspell")
-        .arg("-d")
-        .arg(LANG)
-        .arg("-l")
-        .arg(CHECK_FILE)
-        .output()
-        .expect("msg")
-        .stdout;
-    if o.is_empty() {
-        return true;
+///
+/// Offer to add `words` to the dictionary of the project, return if they have been added.
+///
+fn learn(r: &str, words: &[String]) -> bool {
+    if confirm(
+        format!("Add these words to zazen/{DICTIONARY_FILE} ?").as_str(),
+        false,
+    )
+    .eq(&false)
+    {
+        return false;
+    }
+    match spelling::learn(r, words) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("{e}");
+            false
+        }
     }
-    arrange_commit()
 }
+
+fn check_commit(r: &str, checker: &mut Checker, sentence: &str) -> bool {
+    check(r, checker, sentence, "the commit message")
+}
+
 fn print_diff(diff: &Diff<'_>) -> Result<(), git2::Error> {
     let stats: DiffStats = diff.stats().expect("msg");
     let x = diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
@@ -309,122 +309,769 @@ fn print_diff(diff: &Diff<'_>) -> Result<(), git2::Error> {
 
     print!(
         "\n  {} files changed, {} insertions(+), {} deletion(-)\n",
-        stats.files_changed() - 1,
+        stats.files_changed(),
         stats.insertions(),
         stats.deletions(),
     );
     x
 }
+fn print_recommendations(recommendations: &[Recommendation]) {
+    if recommendations.is_empty() {
+        return;
+    }
+    println!("\n  {}", "Recommended commit types :".cyan());
+    for r in recommendations {
+        println!("    {r}");
+    }
+    println!();
+}
+///
+/// The changes of the working tree of `repo` not staged yet.
+///
+fn unstaged(repo: &Repository) -> Diff<'_> {
+    let mut opts: DiffOptions = DiffOptions::new();
+    repo.diff_index_to_workdir(
+        None,
+        Some(&mut opts.include_untracked(true).recurse_untracked_dirs(true)),
+    )
+    .expect("msg")
+}
+
+fn show_diff(path: &str) {
+    assert!(print_diff(&unstaged(&open(path))).is_ok());
+}
+
+///
+/// Display the commit types recommended for `changes`, `None` when the classifier rules of `path` are invalid.
+///
+fn recommend(path: &str, changes: &Diff<'_>) -> Option<Vec<Recommendation>> {
+    match classifier::classify(path, changes) {
+        Ok(recommendations) => {
+            print_recommendations(&recommendations);
+            Some(recommendations)
+        }
+        Err(e) => {
+            eprintln!("{}", e.red());
+            None
+        }
+    }
+}
+
+///
+/// The commit types recommended for the changes staged in `index`, the changes between HEAD and the index.
+///
+fn staged_recommendations(path: &str, index: &Index) -> Vec<Recommendation> {
+    let repo: Repository = open(path);
+    let head: Option<git2::Tree<'_>> = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let staged: Diff<'_> = match repo.diff_tree_to_index(head.as_ref(), Some(index), None) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("failed to read the staged changes : {}", e.message());
+            return Vec::new();
+        }
+    };
+    recommend(path, &staged).unwrap_or_default()
+}
+
 fn diff(path: &str) -> bool {
     let repo: Repository = open(path);
-    let mut opts: DiffOptions = DiffOptions::new();
-    let changes: Diff<'_> = repo
-        .diff_index_to_workdir(
-            None,
-            Some(&mut opts.include_untracked(true).recurse_untracked_dirs(true)),
-        )
-        .expect("msg");
+    let changes: Diff<'_> = unstaged(&repo);
     assert!(print_diff(&changes).is_ok());
+    recommend(path, &changes).is_some()
+}
+fn add(path: &str, files: &[String], all: bool, patch: bool, unstage: bool) -> Option<Index> {
+    let repo: Repository = open(path);
+    let (staged, _) = staging::entries(&repo).expect("Failed to get status");
+    if unstage && staged.is_empty().eq(&false) {
+        staging::print_entries(&staged, &[]);
+        let reset: Vec<Entry> = MultiSelect::new("Select files to unstage:", staged)
+            .prompt()
+            .unwrap_or_else(cancelled);
+        staging::unstage(&repo, &reset).expect("Failed to unstage the files");
+    }
+    let (staged, unstaged) = staging::entries(&repo).expect("Failed to get status");
+    staging::print_entries(&staged, &unstaged);
+    let selected: Vec<Entry> = if all {
+        unstaged
+    } else if files.is_empty() {
+        if unstaged.is_empty() {
+            Vec::new()
+        } else {
+            MultiSelect::new("Select files to add:", unstaged)
+                .prompt()
+                .unwrap_or_else(cancelled)
+        }
+    } else {
+        files
+            .iter()
+            .map(|f| {
+                unstaged
+                    .iter()
+                    .find(|e| e.path.eq(f) || e.old_path.as_ref().is_some_and(|o| o.eq(f)))
+                    .cloned()
+                    .unwrap_or_else(|| Entry {
+                        kind: Kind::Modified,
+                        path: f.clone(),
+                        old_path: None,
+                    })
+            })
+            .collect()
+    };
+    if selected.is_empty() && staged.is_empty() {
+        println!("No files to add.");
+        return None;
+    }
+    let mut index = repo.index().expect("msg");
+    for e in &selected {
+        if patch
+            && e.kind.eq(&Kind::Modified)
+            && staging::stage_hunks(&repo, &mut index, e.path.as_str())
+                .expect("failed to stage the hunks")
+        {
+            continue;
+        }
+        staging::stage(&mut index, e).expect("msg");
+    }
+    if patch
+        && staging::preview(&repo, &index)
+            .expect("failed to preview the changes")
+            .eq(&false)
+    {
+        println!("Nothing has been staged.");
+        return None;
+    }
+    index.write().expect("msg");
+
+    println!("Added {} files to the index.", selected.len());
+    Some(index)
+}
+
+fn unstage(path: &str, files: &[String]) -> bool {
+    let repo: Repository = open(path);
+    let (staged, _) = staging::entries(&repo).expect("Failed to get status");
+    if staged.is_empty() {
+        println!("No files to unstage.");
+        return true;
+    }
+    let reset: Vec<Entry> = if files.is_empty() {
+        MultiSelect::new("Select files to unstage:", staged)
+            .prompt()
+            .unwrap_or_else(cancelled)
+    } else {
+        staged
+            .into_iter()
+            .filter(|e| {
+                files
+                    .iter()
+                    .any(|f| e.path.eq(f) || e.old_path.as_ref().is_some_and(|o| o.eq(f)))
+            })
+            .collect()
+    };
+    if staging::unstage(&repo, &reset).is_err() {
+        eprintln!("Failed to unstage the files");
+        return false;
+    }
+    println!("Removed {} files from the index.", reset.len());
     true
 }
-fn add(path: &str) -> Option<Index> {
+
+fn committed(repo: &Repository, created: Result<git2::Oid, String>) -> bool {
+    match created {
+        Ok(oid) => {
+            let summary: String = repo
+                .find_commit(oid)
+                .map(|c| c.summary().unwrap_or_default().to_string())
+                .unwrap_or_default();
+            let branch: String = repo
+                .head()
+                .ok()
+                .and_then(|h| h.shorthand().map(String::from))
+                .unwrap_or_else(|| String::from("HEAD"));
+            println!(
+                "[{branch} {}] {}",
+                oid.to_string().chars().take(7).collect::<String>().yellow(),
+                summary
+            );
+            true
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            false
+        }
+    }
+}
+
+fn msg(m: &str, r: &str, index: &mut Index, verify: bool) -> bool {
+    let repo: Repository = open(r);
+    committed(&repo, signing::commit(&repo, index, m, verify))
+}
+
+fn amend(path: &str, args: &AmendArgs) -> bool {
     let repo: Repository = open(path);
-    let statuses: Statuses<'_> = repo.statuses(None).expect("Failed to get status");
-    let mut file_options: Vec<String> = Vec::new();
-    for entry in &statuses {
-        let status: Status = entry.status();
-        if status.is_wt_new() || status.is_wt_modified() {
-            let path = entry.path().unwrap_or_default();
-            file_options.push(path.to_string());
+    let Ok(head) = repo.head().and_then(|h| h.peel_to_commit()) else {
+        eprintln!("There is no commit to amend");
+        return false;
+    };
+    let pushed: bool = rewrite::unpushed(&repo).is_ok_and(|u| u.contains(&head.id()).eq(&false));
+    if pushed && confirm("The last commit has been pushed, amend it anyway ?", false).eq(&false) {
+        return false;
+    }
+    let Some(config) = configuration(path) else {
+        return false;
+    };
+    let style: Style = args.style.unwrap_or(config.commit.style);
+    let template: Template = match commit_template(path, style) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Invalid commit template {e}");
+            return false;
         }
+    };
+    let Some(mut linter) = linter(path) else {
+        return false;
+    };
+    let Some(translator) = translator(path) else {
+        return false;
+    };
+    if args.no_verify.eq(&false) && zuu(path).eq(&false) {
+        return false;
     }
-    if file_options.is_empty() {
-        println!("No files to add.");
-        None
+    let (_, unstaged) = staging::entries(&repo).expect("Failed to get status");
+    let stage: bool = args.all
+        || args.files.is_empty().eq(&false)
+        || (unstaged.is_empty().eq(&false)
+            && confirm("Add changes to the amended commit ?", false));
+    let staged: Option<Index> = if stage {
+        add(path, &args.files, args.all, false, false)
     } else {
-        let selected_files: Vec<String> = MultiSelect::new("Select files to add:", file_options)
-            .prompt()
-            .expect("Fail");
-        let mut index = repo.index().expect("msg");
-        for file in &selected_files {
-            index.add_path(file.as_ref()).expect("msg");
+        None
+    };
+    let mut index: Index = staged.unwrap_or_else(|| repo.index().expect("msg"));
+    let mut current: CommitMessage = CommitMessage::from_commit(&head).unwrap_or_else(|| {
+        let author = head.author();
+        CommitMessage {
+            summary: rewrite::subject(&head),
+            author: author.name().unwrap_or_default().to_string(),
+            email: author.email().unwrap_or_default().to_string(),
+            date: Utc::now().date_naive().to_string(),
+            ..CommitMessage::default()
         }
-        index.write().expect("msg");
+    });
+    translator.translate(&mut current, Style::Cosmic);
+    let mut m: CommitMessage = CommitMessage {
+        commit_type: get_commit_types(translator.types(), &[], current.commit_type.as_str()),
+        scope: get_scope(path, &mut linter, current.scope.as_str()),
+        summary: get_summary(path, &mut linter, current.summary.as_str()),
+        why: get_why(path, &mut linter, &current.why),
+        ..current
+    };
+    get_footer(path, &mut linter, config.commit.signoff, &mut m);
+    translator.translate(&mut m, style);
+    let message: String = template
+        .render(&m.context())
+        .expect("the template has been checked");
+    let Some(message) = review(path, &linter, message, args.edit, true) else {
+        return false;
+    };
+    committed(
+        &repo,
+        signing::amend(
+            &repo,
+            &mut index,
+            message.as_str(),
+            args.no_verify.eq(&false),
+        ),
+    )
+}
 
-        println!("Added {} files to the index.", selected_files.len());
-        Some(index)
+fn fixup_target<'r>(repo: &'r Repository, commit: Option<&str>) -> Option<Commit<'r>> {
+    if let Some(rev) = commit {
+        return repo
+            .revparse_single(rev)
+            .and_then(|o| o.peel_to_commit())
+            .map_err(|e| eprintln!("{rev} is not a commit : {}", e.message()))
+            .ok();
+    }
+    let unpushed: Vec<git2::Oid> = match rewrite::unpushed(repo) {
+        Ok(u) => u,
+        Err(e) => {
+            eprintln!("{}", e.message());
+            return None;
+        }
+    };
+    let commits: Vec<Commit<'_>> = unpushed
+        .into_iter()
+        .rev()
+        .filter_map(|o| repo.find_commit(o).ok())
+        .filter(|c| {
+            let s: String = rewrite::subject(c);
+            s.starts_with(rewrite::FIXUP).eq(&false) && s.starts_with(rewrite::SQUASH).eq(&false)
+        })
+        .collect();
+    if commits.is_empty() {
+        eprintln!("There is no unpushed commit to fix");
+        return None;
     }
+    let options: Vec<String> = commits
+        .iter()
+        .map(|c| format!("{} {}", short_id(c), rewrite::subject(c)))
+        .collect();
+    let picked: String = Select::new("Select the commit to fix :", options.clone())
+        .prompt()
+        .unwrap_or_else(cancelled);
+    let position: usize = options.iter().position(|o| o.eq(&picked))?;
+    commits.into_iter().nth(position)
 }
 
-fn msg(m: &str, r: &str) -> bool {
-    Command::new("git")
-        .arg("commit")
-        .arg("-m")
-        .arg(m)
-        .current_dir(r)
-        .spawn()
-        .expect("git")
-        .wait()
-        .unwrap()
-        .success()
-}
-fn commit(path: &str) -> bool {
-    assert!(zuu(path));
-    assert!(diff(path));
-    let index = add(path);
-    if index.is_none() {
+fn fixup(path: &str, args: &FixupArgs) -> bool {
+    let repo: Repository = open(path);
+    let Some(target) = fixup_target(&repo, args.commit.as_deref()) else {
+        return false;
+    };
+    if args.no_verify.eq(&false) && zuu(path).eq(&false) {
+        return false;
+    }
+    show_diff(path);
+    let Some(mut index) = add(path, &args.files, args.all, args.patch, args.unstage) else {
         return false;
+    };
+    let prefix: &str = if args.squash {
+        rewrite::SQUASH
+    } else {
+        rewrite::FIXUP
+    };
+    let mut message: String = format!("{prefix}{}", rewrite::subject(&target));
+    if args.squash {
+        let Some(mut linter) = linter(path) else {
+            return false;
+        };
+        for w in get_why(path, &mut linter, &[]) {
+            message.push_str(format!("\n\n* {w}").as_str());
+        }
     }
     msg(
-        COMMIT_TEMPLATE
-            .replace("%type%", get_commit_types().as_str())
-            .replace("%scope%", get_scope().as_str())
-            .replace("%summary%", get_summary().as_str())
-            .replace("%why%", get_why().as_str())
-            .replace("%footer%", get_footer().as_str())
-            .replace("%date%", Utc::now().date_naive().to_string().as_str())
-            .replace("%author%", name().as_str())
-            .replace("%email%", email().as_str())
-            .as_str(),
+        message.as_str(),
         path,
+        &mut index,
+        args.no_verify.eq(&false),
     )
 }
-fn arrange_commit() -> bool {
-    let _ = Command::new("hunspell")
-        .arg("-d")
-        .arg(LANG)
-        .arg(CHECK_FILE)
-        .spawn()
-        .expect("Missing dic")
-        .wait()
-        .unwrap()
-        .success();
-    check_commit(
-        read_to_string(CHECK_FILE)
-            .expect("failed to parse zen file")
-            .as_str(),
-    )
+
+fn autosquash(path: &str) -> bool {
+    let repo: Repository = open(path);
+    let steps: Vec<String> = match rewrite::preview(&repo) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("{e}");
+            return false;
+        }
+    };
+    if steps.iter().all(|s| s.starts_with("pick")) {
+        println!("Nothing to autosquash.");
+        return true;
+    }
+    println!();
+    for s in &steps {
+        println!("  {s}");
+    }
+    println!();
+    if confirm("Rewrite the unpushed history ?", false).eq(&false) {
+        return true;
+    }
+    let Some(config) = configuration(path) else {
+        return false;
+    };
+    let template: Template = match commit_template(path, config.commit.style) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Invalid commit template {e}");
+            return false;
+        }
+    };
+    match rewrite::autosquash(&repo, &template) {
+        Ok(n) => {
+            println!("{n} commits melted into their target.");
+            true
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            false
+        }
+    }
 }
 
-fn arrange(path: &str) -> bool {
-    let _ = Command::new("hunspell")
-        .arg("-d")
-        .arg(LANG)
-        .arg(path)
-        .spawn()
-        .expect("Missing dic")
-        .wait()
-        .unwrap()
-        .success();
-    check(
-        read_to_string(path)
-            .expect("failed to parse zen file")
-            .as_str(),
+fn display_signing(r: &str) -> bool {
+    let repo: Repository = open(r);
+    let config: git2::Config = repo.config().expect("failed to read the git configuration");
+    let enabled: bool = config.get_bool("commit.gpgsign").unwrap_or(false);
+    println!(
+        "commit.gpgsign : {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    match signing::Signer::from_config(&config) {
+        Ok(s) => {
+            println!("{s}");
+            true
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            false
+        }
+    }
+}
+///
+/// The lint rules of the repository `path`, the error being printed when they cannot be loaded.
+///
+fn linter(path: &str) -> Option<Linter> {
+    Linter::new(path).map_err(|e| eprintln!("{e}")).ok()
+}
+
+fn configuration(path: &str) -> Option<Config> {
+    config::load(path).map_err(|e| eprintln!("{e}")).ok()
+}
+
+fn translator(path: &str) -> Option<Translator> {
+    Translator::new(path).map_err(|e| eprintln!("{e}")).ok()
+}
+
+fn cargo_package() -> Option<cargo_metadata::Package> {
+    workspace::package().map_err(|e| eprintln!("{e}")).ok()
+}
+
+fn member(repo: &Repository) -> Result<Option<Member>, ()> {
+    workspace::member(repo).map_err(|e| eprintln!("{e}"))
+}
+
+fn commit(path: &str, args: &CommitArgs) -> bool {
+    let Some(mut linter) = linter(path) else {
+        return false;
+    };
+    let Some(config) = configuration(path) else {
+        return false;
+    };
+    let Some(translator) = translator(path) else {
+        return false;
+    };
+    if valid_commit_args(&linter, &translator, args).eq(&false) {
+        return false;
+    }
+    let style: Style = args.style.unwrap_or(config.commit.style);
+    let template: Template = match commit_template(path, style) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Invalid commit template {e}");
+            return false;
+        }
+    };
+    if args.no_verify.eq(&false) && zuu(path).eq(&false) {
+        return false;
+    }
+    show_diff(path);
+    let Some(mut index) = add(path, &args.files, args.all, args.patch, args.unstage) else {
+        return false;
+    };
+    let recommendations: Vec<Recommendation> = staged_recommendations(path, &index);
+    let repo: Repository = open(path);
+    let prompted: bool = args.commit_type.is_none()
+        || args.scope.is_none()
+        || args.summary.is_none()
+        || args.why.is_empty();
+    let (mut d, resume) = if prompted {
+        choose_draft(&repo)
+    } else {
+        (Draft::default(), false)
+    };
+    let prior: CommitMessage = d.message.clone();
+    let skip = |d: &Draft, step: Step| resume && d.has(step);
+    d.message.commit_type = match &args.commit_type {
+        Some(t) => translator.name(t).expect("checked before"),
+        None if skip(&d, Step::Type) => prior.commit_type.clone(),
+        None => get_commit_types(
+            translator.types(),
+            &recommendations,
+            prior.commit_type.as_str(),
+        ),
+    };
+    save_draft(&repo, &mut d, Step::Type);
+    d.message.scope = match &args.scope {
+        Some(s) => s.clone(),
+        None if skip(&d, Step::Scope) => prior.scope.clone(),
+        None => get_scope(path, &mut linter, prior.scope.as_str()),
+    };
+    save_draft(&repo, &mut d, Step::Scope);
+    d.message.summary = match &args.summary {
+        Some(s) => s.clone(),
+        None if skip(&d, Step::Summary) => prior.summary.clone(),
+        None => get_summary(path, &mut linter, prior.summary.as_str()),
+    };
+    save_draft(&repo, &mut d, Step::Summary);
+    d.message.why = if args.why.is_empty().eq(&false) {
+        args.why.clone()
+    } else if skip(&d, Step::Why) {
+        prior.why.clone()
+    } else {
+        get_why(path, &mut linter, &prior.why)
+    };
+    save_draft(&repo, &mut d, Step::Why);
+    d.message.breaking = merged(&prior.breaking, args.breaking.clone());
+    d.message.fixes = merged(&prior.fixes, args.fixes.iter().map(|f| issue(f)).collect());
+    d.message.closes = merged(
+        &prior.closes,
+        args.closes.iter().map(|c| issue(c)).collect(),
+    );
+    d.message.trailers = merged(
+        &prior.trailers,
+        commit_trailers(args, config.commit.signoff),
+    );
+    if args.summary.is_none() && skip(&d, Step::Footer).eq(&false) {
+        get_footer(path, &mut linter, config.commit.signoff, &mut d.message);
+        save_draft(&repo, &mut d, Step::Footer);
+    }
+    let mut m: CommitMessage = CommitMessage {
+        author: name(),
+        email: email(),
+        date: Utc::now().date_naive().to_string(),
+        ..d.message
+    };
+    translator.translate(&mut m, style);
+    let message: String = template
+        .render(&m.context())
+        .expect("the template has been checked");
+    let Some(message) = review(path, &linter, message, args.edit, prompted) else {
+        return false;
+    };
+    let done: bool = msg(
+        message.as_str(),
         path,
+        &mut index,
+        args.no_verify.eq(&false),
+    );
+    if done {
+        draft::discard(&repo);
+    }
+    done
+}
+
+///
+/// Let the user review `message` in the editor when `edit` is set or when asked after the prompts, then check it with
+/// the lint rules, `None` aborts.
+///
+/// The errors reopen the editor when the message was prompted, the warnings are only displayed.
+///
+fn review(r: &str, linter: &Linter, message: String, edit: bool, prompted: bool) -> Option<String> {
+    let mut text: String = message;
+    let mut editing: bool =
+        edit || (prompted && confirm("Review the message in the editor ?", false));
+    loop {
+        if editing {
+            text = edit_message(r, text.as_str())?;
+        }
+        let violations: Vec<Violation> = linter.lint(text.as_str());
+        print_violations(&violations);
+        if lint::has_errors(&violations).eq(&false) {
+            return Some(text);
+        }
+        if prompted.eq(&false) || confirm("Edit the message to fix the errors ?", true).eq(&false) {
+            eprintln!("The commit message breaks the lint rules, the commit is aborted");
+            return None;
+        }
+        editing = true;
+    }
+}
+
+///
+/// Write `text` in `COMMIT_EDITMSG`, open it in the editor and read it back, `None` when it is emptied.
+///
+fn edit_message(r: &str, text: &str) -> Option<String> {
+    let file = open(r).path().join("COMMIT_EDITMSG");
+    fs::write(&file, format!("{}\n{REVIEW_HELP}", text.trim_end()))
+        .expect("failed to write the commit message");
+    if let Err(e) = edit_file(&file) {
+        eprintln!("{e}");
+        return None;
+    }
+    let text: String = read_to_string(&file)
+        .expect("failed to read the commit message")
+        .lines()
+        .filter(|l| l.starts_with('#').eq(&false))
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string();
+    if text.is_empty() {
+        eprintln!("The commit message is empty, the commit is aborted");
+        return None;
+    }
+    Some(format!("{text}\n"))
+}
+
+fn print_violations(violations: &[Violation]) {
+    for v in violations {
+        if v.severity.eq(&lint::Severity::Error) {
+            eprintln!("{}", v.to_string().red());
+        } else {
+            eprintln!("{}", v.to_string().yellow());
+        }
+    }
+}
+
+///
+/// Open `file` in `EDITOR`, or `CRATES_EDITOR` when it is not set, and wait for the editor to exit.
+///
+fn edit_file(file: &Path) -> Result<(), String> {
+    let editor: String = [EDITOR, CRATES_EDITOR]
+        .iter()
+        .find_map(|v| {
+            std::env::var(v)
+                .ok()
+                .filter(|e| e.trim().is_empty().eq(&false))
+        })
+        .ok_or_else(|| {
+            format!("{EDITOR} or {CRATES_EDITOR} must give the editor of the message")
+        })?;
+    let mut words = editor.split_whitespace();
+    let program: &str = words.next().expect("the editor is not empty");
+    let status = Command::new(program)
+        .args(words)
+        .arg(file)
+        .status()
+        .map_err(|e| format!("failed to run {editor} : {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{editor} failed, the commit is aborted"))
+    }
+}
+
+fn merged<T: PartialEq + Clone>(prior: &[T], values: Vec<T>) -> Vec<T> {
+    let mut all: Vec<T> = prior.to_vec();
+    all.extend(values.into_iter().filter(|v| prior.contains(v).eq(&false)));
+    all
+}
+
+///
+/// Offer to resume, edit or discard the draft of an interrupted commit, the draft and whether its answers are kept.
+///
+fn choose_draft(repo: &Repository) -> (Draft, bool) {
+    let Some(d) = draft::load(repo) else {
+        return (Draft::default(), false);
+    };
+    println!(
+        "\n  A commit was interrupted : {}({}): {}\n",
+        d.message.commit_type, d.message.scope, d.message.summary
+    );
+    let choice: &str = Select::new(
+        "What to do with the draft ?",
+        vec![RESUME_DRAFT, EDIT_DRAFT, DISCARD_DRAFT],
     )
+    .prompt()
+    .unwrap_or_else(cancelled);
+    match choice {
+        RESUME_DRAFT => (d, true),
+        EDIT_DRAFT => (d, false),
+        _ => {
+            draft::discard(repo);
+            (Draft::default(), false)
+        }
+    }
+}
+
+fn save_draft(repo: &Repository, d: &mut Draft, step: Step) {
+    if let Err(e) = d.save(repo, step) {
+        eprintln!("{e}");
+    }
+}
+
+///
+/// Stop zazen when a prompt is cancelled, the answers of an interrupted commit are kept in its draft.
+///
+fn cancelled<T>(e: InquireError) -> T {
+    eprintln!("{e}");
+    std::process::exit(1)
+}
+
+fn commit_template(r: &str, style: Style) -> Result<Template, String> {
+    let (name, default) = match style {
+        Style::Cosmic => (COMMIT_TEMPLATE_FILE, COMMIT_TEMPLATE),
+        Style::Conventional => (CONVENTIONAL_TEMPLATE_FILE, CONVENTIONAL_TEMPLATE),
+    };
+    template::load(r, name, default, &CommitMessage::sample().context())
+}
+
+fn commit_trailers(args: &CommitArgs, signoff: bool) -> Vec<(String, String)> {
+    let mut all: Vec<(String, String)> = args
+        .co_author
+        .iter()
+        .map(|a| (String::from(trailers::CO_AUTHORED_BY), a.trim().to_string()))
+        .collect();
+    if args.signoff || signoff {
+        all.push((String::from(trailers::SIGNED_OFF_BY), signer()));
+    }
+    all.extend(args.trailer.iter().filter_map(|t| trailers::parse(t)));
+    all
+}
+
+fn signer() -> String {
+    format!("{} <{}>", name(), email())
+}
+
+fn valid_author(author: &str) -> bool {
+    let author: &str = author.trim();
+    author.ends_with('>')
+        && author
+            .split_once('<')
+            .is_some_and(|(n, e)| n.trim().is_empty().eq(&false) && e.contains('@'))
+}
+
+fn issue(i: &str) -> String {
+    i.trim().trim_start_matches('#').to_string()
+}
+
+fn valid_commit_args(linter: &Linter, translator: &Translator, args: &CommitArgs) -> bool {
+    if let Some(t) = &args.commit_type {
+        if translator.name(t).is_none() {
+            eprintln!("{t} is not a commit type");
+            return false;
+        }
+    }
+    if let Some(scope) = &args.scope {
+        let max: usize = linter.scope_length();
+        if scope.is_empty() || scope.chars().count().gt(&max) {
+            eprintln!("scope must contains between 1 and {max} character");
+            return false;
+        }
+    }
+    if let Some(summary) = &args.summary {
+        let max: usize = linter.summary_length();
+        if summary.is_empty() || summary.chars().count().gt(&max) {
+            eprintln!("Summary must be contains between 1 and {max} chararacter");
+            return false;
+        }
+    }
+    let max: usize = linter.why_length();
+    if args
+        .why
+        .iter()
+        .any(|w| w.is_empty() || w.chars().count().gt(&max))
+    {
+        eprintln!(
+            "The reasoning behind the change must be contains between 1 and {max} chararacter"
+        );
+        return false;
+    }
+    if let Some(a) = args.co_author.iter().find(|a| valid_author(a).eq(&false)) {
+        eprintln!("{a} is not a co-author, use Name <email>");
+        return false;
+    }
+    if let Some(t) = args.trailer.iter().find(|t| trailers::parse(t).is_none()) {
+        eprintln!("{t} is not a trailer, use Key: value");
+        return false;
+    }
+    true
 }
-fn program_or_lib() -> String {
-    if read_to_string("Cargo.toml")
+
+fn program_or_lib(package: &cargo_metadata::Package) -> String {
+    if read_to_string(package.manifest_path.as_std_path())
         .expect("no cargo project")
         .contains("lib")
     {
@@ -433,7 +1080,10 @@ fn program_or_lib() -> String {
         String::from("software")
     }
 }
-fn create_changelog(r: &str) -> bool {
+fn create_changelog(r: &str, style: Option<Style>) -> bool {
+    let Some(cargo) = cargo_package() else {
+        return false;
+    };
     if Path::new(format!("{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}logs").as_str())
         .is_dir()
         .eq(&false)
@@ -445,87 +1095,228 @@ fn create_changelog(r: &str) -> bool {
     }
     let filename: String = format!(
         "{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}logs{MAIN_SEPARATOR_STR}{}-{}-changes.md",
-        project(),
-        version()
+        project(&cargo),
+        version(&cargo)
     );
-    let mut f: File = File::create(filename.as_str()).expect("failed to create file");
-    writeln!(
-        f,
-        "# 🚀 {} {} released\n\nToday the `{}`, we are very happy to present the **{}** version of our `{}` {} !\n\nThis release marks a significant step forward for our {} {}.\n\n## Demonstration\n\n{}\n\n## What it's?\n\nIt's {}\n\n## What we do ?\n\n- {}\n\n## Our team\n\n- {}\n\n## Links\n\n- [Source code]({})\n- [Home]({})\n- [Issues]({})\n- [Pull Request]({})\n- [Discussions]({})\n- [Wiki]({})\n- [Projects]({})\n- [Releases]({})\n- [Crates.io](https://crates.io/crates/{}/{})\n",
-        project(),
-        version(),
-        Utc::now().date_naive(),
-        version(),
-        project(),
-        program_or_lib(),
-        program_or_lib(),
-        project(),
-        project(),
-        description(),
-        keywords().join("\n- "),
-        authors().join("\n- "),
-        repository(),
-        homepage(),
-        issues(),
-        pulls_request(),
-        discussions(),
-        wiki(),
-        projects(),
-        releases(),
-        project(),
-        version()
-    )
-    .expect("msg");
-    let repo: Repository = open(".");
-    let mut revwalk = repo.revwalk().expect("msg");
-    revwalk.push_head().expect("msg");
-    for oid in revwalk {
-        let oid = oid.expect("msg");
-        let commit = repo.find_commit(oid).expect("msg");
-        let message = commit.message().unwrap_or_default();
-        let relevant_lines: Vec<&str> = message.lines().collect();
-        for l in relevant_lines {
-            let line = l.trim();
-            if line.is_empty() {
-                continue;
-            }
-            if line.contains('(') {
-                writeln!(f, "- {line}").expect("msg");
-            }
-            if line.contains("The following changes were made :") {
-                writeln!(f, "\t- {line}").expect("msg");
-            }
-            if line.contains('*') {
-                writeln!(f, "\t\t- {}", line.to_string().replace('*', "").trim()).expect("msg");
-            }
-            if line.contains("by") {
-                writeln!(f, "\t- {}", line.to_string().replace('*', "").trim()).expect("msg");
-            }
-            if line.contains('#') {
-                writeln!(f, "\t\t- {}", line.to_string().replace('*', "").trim()).expect("msg");
-            }
+    let template: Template = match announcement_template(r) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Invalid announcement template {e}");
+            return false;
+        }
+    };
+    let repo: Repository = open(r);
+    let Ok(member) = member(&repo) else {
+        return false;
+    };
+    let release: Release = match changelog::release(r, &repo, style, member.as_ref()) {
+        Ok(release) => release,
+        Err(e) => {
+            eprintln!("{e}");
+            return false;
+        }
+    };
+    let mut project: Project = package(&cargo);
+    project.readme = read_to_string(readme(&cargo))
+        .expect("readme file not founded")
+        .trim()
+        .replace('#', "##");
+    project.license = read_to_string(license(&cargo))
+        .expect("LICENSE file not founded")
+        .trim()
+        .to_string();
+    let context: Value = changelog::announcement(
+        &project,
+        &release,
+        Utc::now().date_naive().to_string().as_str(),
+    );
+    match template.render(&context) {
+        Ok(announcement) => {
+            fs::write(filename.as_str(), announcement).expect("failed to create file");
+        }
+        Err(e) => {
+            eprintln!("Invalid announcement template {}", e.join("\n\t"));
+            return false;
         }
     }
-    writeln!(
-        f,
-        "\n\n{}\n\n```\n{}\n```\n",
-        read_to_string(readme())
-            .expect("readme file not founded")
-            .trim()
-            .replace('#', "##"),
-        read_to_string(license())
-            .expect("LICENSE file not founded")
-            .trim()
-    )
-    .expect("msg");
     if Path::new("log").exists() {
         remove_file("log").expect("no log file");
     }
     Path::new(filename.as_str()).exists()
 }
 
-fn issues() -> String {
-    let mut x = repository();
+///
+/// Add the changes made since the previous version tag to the CHANGELOG.md of the package, under its version.
+///
+fn keep_changelog(r: &str, style: Option<Style>) -> bool {
+    let Some(cargo) = cargo_package() else {
+        return false;
+    };
+    let repo: Repository = open(r);
+    let Ok(member) = member(&repo) else {
+        return false;
+    };
+    let release: Release = match changelog::release(r, &repo, style, member.as_ref()) {
+        Ok(release) => release,
+        Err(e) => {
+            eprintln!("{e}");
+            return false;
+        }
+    };
+    let path: String = format!(
+        "{}{MAIN_SEPARATOR_STR}{CHANGELOG_MD}",
+        package_dir(r, member.as_ref())
+    );
+    let existing: Option<String> = read_to_string(path.as_str()).ok();
+    let version: String = version(&cargo);
+    let tag: String = changelog::tag_name(release.previous.as_deref(), version.as_str());
+    match changelog::keep_a_changelog(
+        existing.as_deref(),
+        version.as_str(),
+        Utc::now().date_naive().to_string().as_str(),
+        changelog::keep_a_changelog_entries(&release).as_str(),
+        compare_links(&cargo, release.previous.as_deref(), tag.as_str()),
+    ) {
+        Ok(content) => {
+            fs::write(path.as_str(), content).expect("failed to write the changelog");
+            println!("{path} updated with {version}");
+            true
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            false
+        }
+    }
+}
+
+///
+/// Export every version of `r` in `format`, in `zazen/logs/<project>-changelog.<format>`. The announcement and the
+/// Keep a Changelog formats are written by their own actions and cannot be exported.
+///
+fn export_changelog(r: &str, style: Option<Style>, format: Format) -> bool {
+    type Export = fn(&Project, &[changelog::Version]) -> String;
+    let (export, extension): (Export, &str) = match format {
+        Format::Json => (export::json, "json"),
+        Format::Html => (export::html, "html"),
+        Format::Atom => (export::atom, "atom"),
+        Format::Announcement | Format::KeepAChangelog => {
+            eprintln!("{format:?} is not an export format, use json, html or atom");
+            return false;
+        }
+    };
+    let Some(cargo) = cargo_package() else {
+        return false;
+    };
+    let repo: Repository = open(r);
+    let Ok(member) = member(&repo) else {
+        return false;
+    };
+    let project: Project = package(&cargo);
+    let versions: Vec<changelog::Version> =
+        match changelog::history(r, &repo, style, member.as_ref(), project.version.as_str()) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("{e}");
+                return false;
+            }
+        };
+    let content: String = export(&project, &versions);
+    let dir: String = format!("{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}logs");
+    fs::create_dir_all(dir.as_str()).expect("failed to create the logs directory");
+    let filename: String = format!(
+        "{dir}{MAIN_SEPARATOR_STR}{}-changelog.{extension}",
+        project.name
+    );
+    fs::write(filename.as_str(), content).expect("failed to write the changelog");
+    println!("{filename} written");
+    true
+}
+
+///
+/// The directory of the `member` of the workspace in the repository `r`, `r` itself for the package at its root.
+///
+fn package_dir(r: &str, member: Option<&Member>) -> String {
+    member.map_or_else(
+        || r.to_string(),
+        |m| format!("{r}{MAIN_SEPARATOR_STR}{}", m.directory.display()),
+    )
+}
+
+///
+/// The compare urls of the unreleased changes and of the release `tag`, `None` outside GitHub and GitLab.
+///
+fn compare_links(
+    package: &cargo_metadata::Package,
+    previous: Option<&str>,
+    tag: &str,
+) -> Option<(String, String)> {
+    let x: String = package
+        .repository
+        .as_deref()?
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .to_string();
+    let (compare, tags) = if x.contains("github") {
+        (format!("{x}/compare"), format!("{x}/releases/tag"))
+    } else if x.contains("gitlab") {
+        (format!("{x}/-/compare"), format!("{x}/-/tags"))
+    } else {
+        return None;
+    };
+    Some((
+        format!("{compare}/{tag}...HEAD"),
+        previous.map_or_else(
+            || format!("{tags}/{tag}"),
+            |p| format!("{compare}/{p}...{tag}"),
+        ),
+    ))
+}
+
+fn announcement_template(r: &str) -> Result<Template, String> {
+    template::load(
+        r,
+        ANNOUNCEMENT_TEMPLATE_FILE,
+        ANNOUNCEMENT_TEMPLATE,
+        &changelog::announcement(&Project::sample(), &Release::sample(), "date"),
+    )
+}
+
+///
+/// The metadata and the links of the package of the current directory, without its readme and its license text.
+///
+fn package(cargo: &cargo_metadata::Package) -> Project {
+    Project {
+        name: project(cargo),
+        version: version(cargo),
+        description: description(cargo),
+        kind: program_or_lib(cargo),
+        keywords: keywords(cargo),
+        authors: authors(cargo),
+        readme: String::new(),
+        license: String::new(),
+        links: vec![
+            ("repository", repository(cargo)),
+            ("homepage", homepage(cargo)),
+            ("issues", issues(cargo)),
+            ("pulls", pulls_request(cargo)),
+            ("discussions", discussions(cargo)),
+            ("wiki", wiki(cargo)),
+            ("projects", projects(cargo)),
+            ("releases", releases(cargo)),
+            (
+                "crate",
+                format!(
+                    "https://crates.io/crates/{}/{}",
+                    project(cargo),
+                    version(cargo)
+                ),
+            ),
+        ],
+    }
+}
+
+fn issues(package: &cargo_metadata::Package) -> String {
+    let mut x = repository(package);
     if x.contains("github") {
         x.push_str("/issues");
     } else if x.contains("gitlab") {
@@ -534,8 +1325,8 @@ fn issues() -> String {
     x
 }
 
-fn wiki() -> String {
-    let mut x: String = repository();
+fn wiki(package: &cargo_metadata::Package) -> String {
+    let mut x: String = repository(package);
     if x.contains("github") {
         x.push_str("/wiki");
     } else if x.contains("gitlab") {
@@ -543,16 +1334,16 @@ fn wiki() -> String {
     }
     x
 }
-fn projects() -> String {
-    let mut x = repository();
+fn projects(package: &cargo_metadata::Package) -> String {
+    let mut x = repository(package);
     if x.contains("github") {
         x.push_str("/projects");
     }
     x
 }
 
-fn pulls_request() -> String {
-    let mut x = repository();
+fn pulls_request(package: &cargo_metadata::Package) -> String {
+    let mut x = repository(package);
     if x.contains("github") {
         x.push_str("/pulls");
     } else if x.contains("gitlab") {
@@ -561,40 +1352,42 @@ fn pulls_request() -> String {
     x
 }
 
-fn discussions() -> String {
-    let mut x = repository();
+fn discussions(package: &cargo_metadata::Package) -> String {
+    let mut x = repository(package);
     if x.contains("github") {
         x.push_str("/discussions");
     }
     x
 }
 
-fn fmt(r: &str) {
-    assert!(Command::new("cargo")
-        .arg("fmt")
-        .current_dir(r)
-        .spawn()
-        .unwrap()
-        .wait()
-        .unwrap()
-        .success());
+///
+/// Run `program` with `args` in `r`, a program that cannot be started or that fails being reported.
+///
+fn succeeds(program: &str, args: &[&str], r: &str) -> bool {
+    match Command::new(program).args(args).current_dir(r).status() {
+        Ok(status) if status.success() => true,
+        Ok(status) => {
+            eprintln!("{program} failed : {status}");
+            false
+        }
+        Err(e) => {
+            eprintln!("failed to run {program} : {e}");
+            false
+        }
+    }
+}
+
+fn fmt(r: &str) -> bool {
+    let formatted: bool = succeeds("cargo", &["fmt"], r);
     clear();
+    formatted
 }
 
 fn zuu(r: &str) -> bool {
     clear();
     let c: String = format!("{r}{MAIN_SEPARATOR_STR}Cargo.toml");
     if Path::new(c.as_str()).exists() {
-        fmt(r);
-        if Command::new("zuu")
-            .current_dir(".")
-            .spawn()
-            .unwrap()
-            .wait()
-            .unwrap()
-            .success()
-            && verify_readme_part(".")
-        {
+        if fmt(r) && succeeds("zuu", &[], ".") && verify_readme_part(".") {
             clear();
             return true;
         }
@@ -604,14 +1397,12 @@ fn zuu(r: &str) -> bool {
     false
 }
 
-fn version() -> String {
-    let metadata = MetadataCommand::new().no_deps().exec().unwrap();
-    let package = metadata.packages.first().unwrap();
+fn version(package: &cargo_metadata::Package) -> String {
     package.version.to_string()
 }
 
-fn releases() -> String {
-    let mut x = repository();
+fn releases(package: &cargo_metadata::Package) -> String {
+    let mut x = repository(package);
     if x.contains("github") {
         x.push_str("/releases");
     } else if x.contains("gitlab") {
@@ -619,56 +1410,42 @@ fn releases() -> String {
     }
     x
 }
-fn project() -> String {
-    let metadata = MetadataCommand::new().no_deps().exec().unwrap();
-    let package: &cargo_metadata::Package = metadata.packages.first().unwrap();
+fn project(package: &cargo_metadata::Package) -> String {
     package.name.to_string()
 }
 
-fn keywords() -> Vec<String> {
-    let metadata = MetadataCommand::new().no_deps().exec().unwrap();
-    let package: &cargo_metadata::Package = metadata.packages.first().unwrap();
+fn keywords(package: &cargo_metadata::Package) -> Vec<String> {
     package.keywords.clone()
 }
 
-fn homepage() -> String {
-    let metadata = MetadataCommand::new().no_deps().exec().unwrap();
-    let package: &cargo_metadata::Package = metadata.packages.first().unwrap();
-    package.clone().homepage.expect("no homepage")
+fn homepage(package: &cargo_metadata::Package) -> String {
+    package.homepage.clone().expect("no homepage")
 }
 
-fn readme() -> String {
-    let metadata = MetadataCommand::new().no_deps().exec().unwrap();
-    let package: &cargo_metadata::Package = metadata.packages.first().unwrap();
-    package
-        .clone()
-        .readme
-        .expect("no readme define")
-        .to_string()
+fn readme(package: &cargo_metadata::Package) -> String {
+    let readme = package.readme.as_ref().expect("no readme define");
+    package.manifest_path.parent().map_or_else(
+        || readme.to_string(),
+        |d| d.join(readme.as_str()).to_string(),
+    )
 }
 
-fn license() -> String {
-    let metadata = MetadataCommand::new().no_deps().exec().unwrap();
-    let package: &cargo_metadata::Package = metadata.packages.first().unwrap();
-    package
-        .clone()
-        .license_file
-        .expect("no licences define")
-        .to_string()
+fn license(package: &cargo_metadata::Package) -> String {
+    let license = package.license_file.as_ref().expect("no licences define");
+    package.manifest_path.parent().map_or_else(
+        || license.to_string(),
+        |d| d.join(license.as_str()).to_string(),
+    )
 }
 
-fn repository() -> String {
-    let metadata = MetadataCommand::new().no_deps().exec().unwrap();
-    let package: &cargo_metadata::Package = metadata.packages.first().unwrap();
-    package.clone().repository.expect("no repository define")
+fn repository(package: &cargo_metadata::Package) -> String {
+    package.repository.clone().expect("no repository define")
 }
 
 ///
 /// # Panics
 ///
-fn description() -> String {
-    let metadata = MetadataCommand::new().no_deps().exec().unwrap();
-    let package: &cargo_metadata::Package = metadata.packages.first().unwrap();
+fn description(package: &cargo_metadata::Package) -> String {
     package
         .description
         .as_ref()
@@ -676,48 +1453,45 @@ fn description() -> String {
         .to_string()
 }
 
-fn authors() -> Vec<String> {
-    let metadata = MetadataCommand::new().no_deps().exec().unwrap();
-    let package: &cargo_metadata::Package = metadata.packages.first().unwrap();
+fn authors(package: &cargo_metadata::Package) -> Vec<String> {
     package.authors.clone()
 }
+///
+/// Clear the terminal, nothing being done when the output is not a terminal.
+///
 fn clear() {
-    if OS.eq("windows") {
-        assert!(Command::new("cls")
-            .current_dir(".")
-            .spawn()
-            .unwrap()
-            .wait()
-            .unwrap()
-            .success());
-    } else {
-        assert!(Command::new("clear")
-            .current_dir(".")
-            .spawn()
-            .unwrap()
-            .wait()
-            .unwrap()
-            .success());
+    if stdout().is_terminal().eq(&false) {
+        return;
+    }
+    let program: &str = if OS.eq("windows") { "cls" } else { "clear" };
+    if let Err(e) = Command::new(program).current_dir(".").status() {
+        eprintln!("failed to run {program} : {e}");
     }
 }
 
-fn commit_types_with_help() -> [&'static str; 68] {
-    let mut x = COMMITS_TYPES;
-    x.sort_unstable();
-    x
-}
-
-fn commit_scope() -> String {
+fn commit_scope(suggestions: &[String], history: &[String], initial: &str, max: usize) -> String {
     let mut scope: String;
     loop {
-        scope = Text::new("Please enter the commit scope : ")
-            .prompt()
-            .unwrap();
+        let mut prompt: Text<'_> = Text::new("Please enter the commit scope : ")
+            .with_autocomplete(ScopeCompleter::new(suggestions, history))
+            .with_initial_value(initial)
+            .with_help_message(
+                "↑↓ to move, tab to complete, the staged files suggest the first scopes",
+            );
+        if let Some(s) = suggestions.first() {
+            prompt = prompt.with_placeholder(s.as_str());
+        }
+        scope = prompt.prompt().unwrap_or_else(cancelled);
+        if scope.is_empty() {
+            if let Some(s) = suggestions.first() {
+                scope.clone_from(s);
+            }
+        }
         if scope.is_empty() {
             continue;
         }
-        if scope.len().gt(&20) {
-            println!("scope can be superior to 20 character");
+        if scope.chars().count().gt(&max) {
+            println!("scope can be superior to {max} character");
             continue;
         }
         if confirm(
@@ -730,40 +1504,44 @@ fn commit_scope() -> String {
     scope
 }
 
-fn get_commit_types() -> String {
-    let mut t: String;
+fn get_commit_types(
+    types: &[CommitType],
+    recommendations: &[Recommendation],
+    current: &str,
+) -> String {
+    let cursor: usize = commit_types::find(types, current)
+        .map(|c| c.name.as_str())
+        .or_else(|| recommendations.first().map(|x| x.commit_type.name.as_str()))
+        .and_then(|name| types.iter().position(|t| t.name.eq(name)))
+        .unwrap_or_default();
+    let mut t: CommitType;
     loop {
-        t = Select::new(
-            "Please enter the commit type : ",
-            commit_types_with_help().to_vec(),
-        )
-        .prompt()
-        .unwrap()
-        .to_string();
-        if t.is_empty() {
-            continue;
-        }
-        if confirm(format!("Really use the commit type : {t}").as_str(), false) {
+        t = Select::new("Please enter the commit type : ", types.to_vec())
+            .with_starting_cursor(cursor)
+            .prompt()
+            .unwrap_or_else(cancelled);
+        if confirm(
+            format!("Really use the commit type : {}", t.name).as_str(),
+            false,
+        ) {
             break;
         }
     }
-    let x: Vec<&str> = t.split(':').collect();
-    let mut s: String = String::from("\n");
-    s.push_str((*x.first().unwrap()).to_string().as_str());
-    s
+    t.name
 }
 
-fn commit_summary() -> String {
+fn commit_summary(initial: &str, max: usize) -> String {
     let mut summary: String;
     loop {
         summary = Text::new("Please enter the commit summary : ")
+            .with_initial_value(initial)
             .prompt()
-            .unwrap();
+            .unwrap_or_else(cancelled);
         if summary.is_empty() {
             continue;
         }
-        if summary.len().gt(&50) {
-            println!("Summary must be contains less than 50 chararacter");
+        if summary.chars().count().gt(&max) {
+            println!("Summary must be contains less than {max} chararacter");
             continue;
         }
         if confirm(format!("Use the summary : {summary}").as_str(), false) {
@@ -773,35 +1551,46 @@ fn commit_summary() -> String {
     summary
 }
 
-fn commit_why() -> String {
-    let mut why: String = String::new();
+fn commit_why(initial: &[String], max: usize) -> Vec<String> {
+    let mut why: Vec<String> = Vec::new();
     loop {
         let w = Text::new("Please explain the reasoning behind the change : ")
+            .with_initial_value(initial.get(why.len()).map_or("", String::as_str))
             .prompt()
-            .unwrap();
+            .unwrap_or_else(cancelled);
         if w.is_empty() {
             continue;
         }
-        if w.len().gt(&50) {
-            println!("The reasoning behind the change must be contains less than 50 chararacter");
+        if w.chars().count().gt(&max) {
+            println!(
+                "The reasoning behind the change must be contains less than {max} chararacter"
+            );
             continue;
         }
-        why.push_str(format!("\n\t\t* {w}").as_str());
-        if confirm("Continue to write the changes : ", false) {
+        why.push(w);
+        if confirm(
+            "Continue to write the changes : ",
+            why.len().lt(&initial.len()),
+        ) {
             continue;
         }
         break;
     }
     why
 }
-fn commit_footer() -> String {
-    let mut footer: String = String::new();
-    if confirm("Code has breaking changes ?", false) {
-        footer.push_str("\n\tThe following changes break :\n");
+fn commit_footer(r: &str, m: &mut CommitMessage, signoff: bool) {
+    let breaking: Vec<String> = std::mem::take(&mut m.breaking);
+    let fixes: Vec<String> = std::mem::take(&mut m.fixes);
+    let closes: Vec<String> = std::mem::take(&mut m.closes);
+    if confirm(
+        "Code has breaking changes ?",
+        breaking.is_empty().eq(&false),
+    ) {
         loop {
             let b = Text::new("Please enter the breaking change description: ")
+                .with_initial_value(breaking.get(m.breaking.len()).map_or("", String::as_str))
                 .prompt()
-                .unwrap();
+                .unwrap_or_else(cancelled);
             if b.is_empty() {
                 continue;
             }
@@ -809,105 +1598,187 @@ fn commit_footer() -> String {
                 format!("Use breaking change description : {b}").as_str(),
                 false,
             ) {
-                footer.push_str(format!("\n\t\t* {b}\n").as_str());
-                if confirm("Add a new description line ?", false).eq(&true) {
+                m.breaking.push(b);
+                if confirm(
+                    "Add a new description line ?",
+                    m.breaking.len().lt(&breaking.len()),
+                )
+                .eq(&true)
+                {
                     continue;
                 }
                 break;
             }
         }
     }
-    if confirm("Code has resolving issues ?", false) {
-        footer.push_str("\n\tThe commit resolve their issues :\n");
+    if confirm("Code has resolving issues ?", fixes.is_empty().eq(&false)) {
         loop {
-            footer.push_str("\n\t\tFixes ");
-            loop {
-                let f = Text::new("Please enter the issue number : ")
-                    .prompt()
-                    .unwrap();
-                if f.is_empty() {
-                    continue;
-                }
-                footer.push_str(format!("#{f}\n").as_str());
-                break;
+            m.fixes.push(issue_number(fixes.get(m.fixes.len())));
+            if confirm(
+                "Code resolving an other issues ?",
+                m.fixes.len().lt(&fixes.len()),
+            ) {
+                continue;
             }
-            if confirm("Code resolving an other issues ?", false) {
+            break;
+        }
+    }
+    if confirm("Code close an issue ?", closes.is_empty().eq(&false)) {
+        loop {
+            m.closes.push(issue_number(closes.get(m.closes.len())));
+            if confirm(
+                "Code resolve an other issue ?",
+                m.closes.len().lt(&closes.len()),
+            ) {
                 continue;
             }
             break;
         }
     }
-    if confirm("Code close an issue ?", false) {
-        footer.push_str("\n\tThe commit close their issues :\n");
+    commit_trailer_prompts(r, m, signoff);
+}
+
+fn commit_trailer_prompts(r: &str, m: &mut CommitMessage, signoff: bool) {
+    let taken: Vec<(String, String)> = std::mem::take(&mut m.trailers);
+    let is = |k: &str, key: &str| k.eq_ignore_ascii_case(key);
+    let co_authors: Vec<String> = taken
+        .iter()
+        .filter(|(k, _)| is(k, trailers::CO_AUTHORED_BY))
+        .map(|(_, v)| v.clone())
+        .collect();
+    let signed: Option<String> = taken
+        .iter()
+        .find(|(k, _)| is(k, trailers::SIGNED_OFF_BY))
+        .map(|(_, v)| v.clone());
+    let others: Vec<String> = taken
+        .iter()
+        .filter(|(k, _)| {
+            is(k, trailers::CO_AUTHORED_BY).eq(&false) && is(k, trailers::SIGNED_OFF_BY).eq(&false)
+        })
+        .map(|(k, v)| format!("{k}: {v}"))
+        .collect();
+    let mut people: Vec<String> = co_authors.clone();
+    people.extend(
+        trailers::authors(r)
+            .into_iter()
+            .filter(|a| co_authors.contains(a).eq(&false)),
+    );
+    if confirm("Code has co-authors ?", co_authors.is_empty().eq(&false)) {
+        if people.is_empty().eq(&false) {
+            let defaults: Vec<usize> = (0..co_authors.len()).collect();
+            for a in MultiSelect::new("Select the co-authors : ", people)
+                .with_default(&defaults)
+                .prompt()
+                .unwrap_or_else(cancelled)
+            {
+                m.trailers.push((String::from(trailers::CO_AUTHORED_BY), a));
+            }
+        }
+        while confirm("Add a co-author missing from the history ?", false) {
+            let a: String = Text::new("Please enter the co-author as Name <email> : ")
+                .prompt()
+                .unwrap_or_else(cancelled);
+            if valid_author(a.as_str()) {
+                m.trailers
+                    .push((String::from(trailers::CO_AUTHORED_BY), a.trim().to_string()));
+            } else {
+                eprintln!("{a} is not a co-author, use Name <email>");
+            }
+        }
+    }
+    if confirm("Sign off the commit ?", signed.is_some() || signoff) {
+        m.trailers.push((
+            String::from(trailers::SIGNED_OFF_BY),
+            signed.unwrap_or_else(signer),
+        ));
+    }
+    if confirm("Code has other trailers ?", others.is_empty().eq(&false)) {
+        let mut count: usize = 0;
         loop {
-            footer.push_str("\n\t\tCloses ");
-            loop {
-                let f = Text::new("Please enter the issue number : ")
-                    .prompt()
-                    .unwrap();
-                if f.is_empty() {
+            let t: String = Text::new("Please enter the trailer as Key: value : ")
+                .with_initial_value(others.get(count).map_or("", String::as_str))
+                .prompt()
+                .unwrap_or_else(cancelled);
+            match trailers::parse(t.as_str()) {
+                Some(trailer) => {
+                    m.trailers.push(trailer);
+                    count += 1;
+                }
+                None => {
+                    eprintln!("{t} is not a trailer, use Key: value");
                     continue;
                 }
-                footer.push_str(format!("#{f}\n").as_str());
-                break;
             }
-            if confirm("Code resolve an other issue ?", false) {
+            if confirm("Add an other trailer ?", count.lt(&others.len())) {
                 continue;
             }
             break;
         }
     }
-    footer
 }
 
-fn get_scope() -> String {
-    let mut scope: String;
+fn issue_number(initial: Option<&String>) -> String {
     loop {
-        scope = commit_scope();
-        if check_commit(scope.as_str()) {
+        let f = Text::new("Please enter the issue number : ")
+            .with_initial_value(initial.map_or("", String::as_str))
+            .prompt()
+            .unwrap_or_else(cancelled);
+        if f.is_empty() {
+            continue;
+        }
+        return issue(f.as_str());
+    }
+}
+
+fn get_scope(r: &str, linter: &mut Linter, initial: &str) -> String {
+    let max: usize = linter.scope_length();
+    let suggestions: Vec<String> = scope::suggestions(r, max);
+    let history: Vec<String> = scope::history(r, max);
+    let mut scope: String = initial.to_string();
+    loop {
+        scope = commit_scope(&suggestions, &history, scope.as_str(), max);
+        if check_commit(r, linter.checker(), scope.as_str()) {
             break;
         }
     }
     scope
 }
 
-fn get_summary() -> String {
-    let mut summary: String;
+fn get_summary(r: &str, linter: &mut Linter, initial: &str) -> String {
+    let mut summary: String = initial.to_string();
     loop {
-        summary = commit_summary();
-        if check_commit(summary.as_str()) {
+        summary = commit_summary(summary.as_str(), linter.summary_length());
+        if check_commit(r, linter.checker(), summary.as_str()) {
             break;
         }
     }
     summary
 }
 
-fn get_why() -> String {
-    let mut why: String;
+fn get_why(r: &str, linter: &mut Linter, initial: &[String]) -> Vec<String> {
+    let mut why: Vec<String> = initial.to_vec();
     loop {
-        why = commit_why();
-        if check_commit(why.as_str()) {
+        why = commit_why(&why, linter.why_length());
+        if check_commit(r, linter.checker(), why.join("\n").as_str()) {
             break;
         }
     }
     why
 }
-fn get_footer() -> String {
-    let mut footer: String;
+fn get_footer(r: &str, linter: &mut Linter, signoff: bool, m: &mut CommitMessage) {
     loop {
-        footer = commit_footer();
-        if check_commit(footer.as_str()) {
+        commit_footer(r, m, signoff);
+        if check_commit(r, linter.checker(), m.footer_prose().join("\n").as_str()) {
             break;
         }
     }
-    footer
 }
 
 fn confirm(msg: &str, default: bool) -> bool {
     Confirm::new(msg)
         .with_default(default)
         .prompt()
-        .unwrap()
+        .unwrap_or_else(cancelled)
         .eq(&true)
 }
 
@@ -944,148 +1815,156 @@ fn name() -> String {
 }
 
 fn remove_branch(b: &str, r: &str) -> bool {
-    Command::new("git")
-        .arg("branch")
-        .arg("-d")
-        .arg(b)
-        .current_dir(r)
-        .spawn()
-        .expect("git")
-        .wait()
-        .unwrap()
-        .success()
+    succeeds("git", &["branch", "-d", b], r)
 }
 fn remove_tag(t: &str, r: &str) -> bool {
-    Command::new("git")
-        .arg("tag")
-        .arg("-d")
-        .arg(t)
-        .current_dir(r)
-        .spawn()
-        .expect("git")
-        .wait()
-        .unwrap()
-        .success()
+    succeeds("git", &["tag", "-d", t], r)
+}
+
+///
+/// Remove each of the `names` with `remove`, every name being tried even after a failure.
+///
+fn remove_all(names: &[String], r: &str, remove: fn(&str, &str) -> bool) -> bool {
+    names
+        .iter()
+        .filter(|n| remove(n.as_str(), r).eq(&false))
+        .count()
+        .eq(&0)
 }
 
 fn send(path: &str) -> bool {
-    Command::new("git")
-        .arg("push")
-        .arg("--all")
-        .current_dir(path)
-        .spawn()
-        .expect("git")
-        .wait()
-        .unwrap()
-        .success()
-        && Command::new("git")
-            .arg("push")
-            .arg("--tags")
-            .current_dir(path)
-            .spawn()
-            .expect("git")
-            .wait()
-            .unwrap()
-            .success()
+    succeeds("git", &["push", "--all"], path) && succeeds("git", &["push", "--tags"], path)
 }
 
+///
+/// Check the spelling of the prose of the readme parts, each unknown word is displayed with its file, line, column and
+/// corrections. Nothing is checked when the package has no readme parts.
+///
 fn verify_readme_part(r: &str) -> bool {
-    let pb: ProgressBar = ProgressBar::new(7)
-        .with_message("checking readme vocabulary")
-        .with_style(
-            ProgressStyle::default_bar()
-                .template("[{bar:50.white}] {msg}")
-                .expect("")
-                .progress_chars("== "),
-        );
-
-    pb.enable_steady_tick(Duration::from_millis(75));
-    for x in README_FILES {
-        pb.set_message(format!("Checking the {x} markdown file"));
-        let content: String = read_to_string(format!(
-            "{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}readme{MAIN_SEPARATOR_STR}{x}"
-        ))
-        .expect("msg");
-        let path: String = format!(
-            "{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}readme{MAIN_SEPARATOR_STR}{x}"
-        );
-        assert!(check(content.trim(), path.as_str()));
-        pb.inc(1);
-        sleep(Duration::from_millis(100));
+    let Some(config) = configuration(r) else {
+        return false;
+    };
+    let spelling: SpellingConfig = config.spelling;
+    let Ok(member) = member(&open(r)) else {
+        return false;
+    };
+    let readme: String = format!(
+        "{}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}readme",
+        package_dir(r, member.as_ref())
+    );
+    if Path::new(readme.as_str()).is_dir().eq(&false) {
+        return true;
     }
-    true
+    let mut parts: Vec<String> = README_FILES.iter().map(|x| (*x).to_string()).collect();
+    let mut others: Vec<String> = WalkDir::new(readme.as_str())
+        .min_depth(2)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|x| x.eq("md")))
+        .filter_map(|e| {
+            e.path()
+                .strip_prefix(readme.as_str())
+                .ok()
+                .map(|p| p.to_string_lossy().replace(MAIN_SEPARATOR_STR, "/"))
+        })
+        .collect();
+    others.sort();
+    parts.extend(others);
+    let mut checkers: HashMap<Vec<String>, Checker> = HashMap::new();
+    let mut unknown: Vec<String> = Vec::new();
+    for x in &parts {
+        let languages: Vec<String> = spelling.readme_languages(x.as_str()).to_vec();
+        let checker: &Checker = checkers.entry(languages).or_insert_with_key(|l| {
+            let checker: Checker = Checker::new(r, l);
+            if let Some(missing) = checker.missing() {
+                eprintln!("{}", missing.yellow());
+            }
+            checker
+        });
+        let path: String = format!("{readme}{MAIN_SEPARATOR_STR}{x}");
+        let Ok(content) = read_to_string(path.as_str()) else {
+            eprintln!("failed to read {path}");
+            return false;
+        };
+        for m in checker.misspelled_markdown(content.as_str()) {
+            let (line, column) = spelling::position(content.as_str(), m.offset);
+            println!(
+                "{path}:{line}:{column} {} {}",
+                m.word.red(),
+                m.suggestions.join(", ").as_str().dimmed()
+            );
+            unknown.push(m.word);
+        }
+    }
+    unknown.is_empty() || learn(r, &unknown)
 }
 fn generate_readme(r: &str) -> bool {
-    let mut f: File = File::create(format!("{r}{MAIN_SEPARATOR_STR}README.md").as_str())
-        .expect("failed to create readme");
+    let Some(cargo) = cargo_package() else {
+        return false;
+    };
+    let Ok(member) = member(&open(r)) else {
+        return false;
+    };
+    let d: String = package_dir(r, member.as_ref());
+    let readme: String = format!("{d}{MAIN_SEPARATOR_STR}README.md");
+    let mut content: String = String::new();
     for x in README_FILES {
         let y: String = format!(
-            "{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}readme{MAIN_SEPARATOR_STR}{x}"
+            "{d}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}readme{MAIN_SEPARATOR_STR}{x}"
         );
-        assert!(writeln!(
-            f,
-            "{}\n",
-            read_to_string(y.as_str())
-                .expect("failed to parse file")
-                .trim()
-        )
-        .is_ok());
-    }
-    Command::new("pandoc")
-        .arg("-s")
-        .arg("-f")
-        .arg("markdown")
-        .arg("-t")
-        .arg("man")
-        .arg("README.md")
-        .arg("-o")
-        .arg(format!("{}.1", project()).as_str())
-        .current_dir(".")
-        .spawn()
-        .expect("pandoc")
-        .wait()
-        .unwrap()
-        .success()
+        match read_to_string(y.as_str()) {
+            Ok(part) => content.push_str(format!("{}\n\n", part.trim()).as_str()),
+            Err(e) => {
+                eprintln!("failed to read {y} : {e}");
+                return false;
+            }
+        }
+    }
+    if let Err(e) = fs::write(readme.as_str(), content) {
+        eprintln!("failed to write {readme} : {e}");
+        return false;
+    }
+    let man: String = format!("{}.1", project(&cargo));
+    succeeds(
+        "pandoc",
+        &[
+            "-s",
+            "-f",
+            "markdown",
+            "-t",
+            "man",
+            "README.md",
+            "-o",
+            man.as_str(),
+        ],
+        d.as_str(),
+    )
 }
 fn show_status(path: &str) {
     let repo: Repository = open(path);
-    let mut opts: StatusOptions = StatusOptions::new();
-    let statuses = repo
-        .statuses(Some(
-            opts.include_ignored(false)
-                .include_untracked(true)
-                .recurse_untracked_dirs(true),
-        ))
-        .expect("Failed to get status");
-
-    for entry in &statuses {
-        let path = entry.path().unwrap_or("unknown");
-        let status = entry.status();
-        println!("{path}: {status:?}");
-    }
+    let (staged, unstaged) = staging::entries(&repo).expect("Failed to get status");
+    staging::print_entries(&staged, &unstaged);
 }
 fn print_readme(r: &str) -> bool {
-    let r: String = format!("{r}{MAIN_SEPARATOR_STR}README.md");
-    assert!(Command::new("bat")
-        .arg("--force-colorization")
-        .arg("--theme")
-        .arg("Visual Studio Dark+")
-        .arg("--style")
-        .arg("plain")
-        .arg(r.as_str())
-        .stdout(File::create(CHECK_FILE).expect("failed to create output file"))
-        .current_dir(".")
-        .spawn()
-        .expect("not bat or no README.md found")
-        .wait()
-        .unwrap()
-        .success());
-    println!(
-        "{}",
-        read_to_string(CHECK_FILE).expect("Failed to print README.md")
+    let Ok(member) = member(&open(r)) else {
+        return false;
+    };
+    let r: String = format!(
+        "{}{MAIN_SEPARATOR_STR}README.md",
+        package_dir(r, member.as_ref())
     );
-    assert!(remove_file(CHECK_FILE).is_ok());
-    true
+    succeeds(
+        "bat",
+        &[
+            "--force-colorization",
+            "--theme",
+            "Visual Studio Dark+",
+            "--style",
+            "plain",
+            r.as_str(),
+        ],
+        ".",
+    )
 }
 fn options() -> Vec<String> {
     let mut options: Vec<String> = Vec::new();
@@ -1127,28 +2006,27 @@ fn clone() -> bool {
     loop {
         url = Text::new("Please enter the repository url : ")
             .prompt()
-            .unwrap();
+            .unwrap_or_else(cancelled);
         if url.is_empty() {
             continue;
         }
         break;
     }
-    Command::new("git")
-        .arg("clone")
-        .arg("--quiet")
-        .arg(url)
-        .current_dir(std::env::var(CRATES_PATH).expect(CRATES_PATH).as_str())
-        .spawn()
-        .expect("missing info")
-        .wait()
-        .unwrap()
-        .success()
+    clone_url(url.as_str())
+}
+
+fn clone_url(url: &str) -> bool {
+    let Ok(dir) = std::env::var(CRATES_PATH) else {
+        eprintln!("{CRATES_PATH} is not set, the repository cannot be cloned");
+        return false;
+    };
+    succeeds("git", &["clone", "--quiet", url], dir.as_str())
 }
 
 fn repo() -> String {
     let r: String = Select::new("Select a repository to manage", repositories())
         .prompt()
-        .unwrap();
+        .unwrap_or_else(cancelled);
     format!(
         "{}{MAIN_SEPARATOR_STR}{r}",
         std::env::var(CRATES_PATH).expect("CRATES_PATH missing")
@@ -1161,6 +2039,57 @@ fn display_branches(r: &str) -> bool {
     }
     true
 }
+fn display_commit_types(r: &str) -> bool {
+    let types: Vec<CommitType> = match commit_types::load(r) {
+        Ok(types) => types,
+        Err(e) => {
+            eprintln!("{e}");
+            return false;
+        }
+    };
+    for t in &types {
+        println!(
+            "{:<2} {:<28} {:<6} {}",
+            t.emoji, t.name, t.semver, t.description
+        );
+        if t.aliases.is_empty().eq(&false) {
+            println!("{:<38}aliases : {}", "", t.aliases.join(", "));
+        }
+    }
+    true
+}
+fn check_templates(r: &str) -> bool {
+    let mut valid: bool = true;
+    for (name, style) in [
+        (COMMIT_TEMPLATE_FILE, Style::Cosmic),
+        (CONVENTIONAL_TEMPLATE_FILE, Style::Conventional),
+    ] {
+        let path: String = template::find(r, name).unwrap_or_else(|| String::from("built-in"));
+        match commit_template(r, style) {
+            Ok(_) => println!("{name} : {path} is valid"),
+            Err(e) => {
+                eprintln!("{name} : {e}");
+                valid = false;
+            }
+        }
+    }
+    let path: String =
+        template::find(r, ANNOUNCEMENT_TEMPLATE_FILE).unwrap_or_else(|| String::from("built-in"));
+    match announcement_template(r) {
+        Ok(_) => println!("{ANNOUNCEMENT_TEMPLATE_FILE} : {path} is valid"),
+        Err(e) => {
+            eprintln!("{ANNOUNCEMENT_TEMPLATE_FILE} : {e}");
+            valid = false;
+        }
+    }
+    valid
+}
+fn display_tags(r: &str) -> bool {
+    for tag in &tags(r) {
+        println!("{tag}");
+    }
+    true
+}
 fn display_status(r: &str) -> bool {
     show_status(r);
     true
@@ -1169,33 +2098,27 @@ fn display_status(r: &str) -> bool {
 fn remove_branches(r: &str) -> bool {
     let branches: Vec<String> = MultiSelect::new("Select branch to remove", branches(r))
         .prompt()
-        .unwrap();
-    for branch in &branches {
-        assert!(remove_branch(branch, r));
-    }
-    true
+        .unwrap_or_else(cancelled);
+    remove_all(&branches, r, remove_branch)
 }
 fn remove_tags(r: &str) -> bool {
     let tags: Vec<String> = MultiSelect::new("Select tags to remove", tags(r))
         .prompt()
-        .unwrap();
-    for tag in &tags {
-        assert!(remove_tag(tag, r));
-    }
-    true
+        .unwrap_or_else(cancelled);
+    remove_all(&tags, r, remove_tag)
 }
 fn code(r: &str) -> bool {
-    Command::new(
-        std::env::var(CRATES_EDITOR)
-            .expect("CRATES_EDITOR missing")
-            .as_str(),
-    )
-    .stdout(Stdio::null())
-    .stderr(Stdio::null())
-    .arg(".")
-    .current_dir(r)
-    .spawn()
-    .is_ok()
+    let Ok(editor) = std::env::var(CRATES_EDITOR) else {
+        eprintln!("{CRATES_EDITOR} is not set, the project cannot be opened");
+        return false;
+    };
+    Command::new(editor.as_str())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .arg(".")
+        .current_dir(r)
+        .spawn()
+        .is_ok()
 }
 fn flow(z: bool, r: &str) {
     loop {
@@ -1213,20 +2136,42 @@ fn flow(z: bool, r: &str) {
         let todo: String = x.unwrap();
         match todo.as_str() {
             COMMIT => {
-                assert!(commit(r));
+                assert!(commit(
+                    r,
+                    &CommitArgs {
+                        unstage: true,
+                        ..CommitArgs::default()
+                    }
+                ));
+            }
+            AMEND => {
+                assert!(amend(r, &AmendArgs::default()));
+            }
+            FIXUP => {
+                assert!(fixup(
+                    r,
+                    &FixupArgs {
+                        unstage: true,
+                        ..FixupArgs::default()
+                    }
+                ));
+            }
+            AUTOSQUASH => {
+                assert!(autosquash(r));
             }
             OPEN_THE_PROJECT => {
                 assert!(code(r));
             }
             CHANGE_OF_PROJECT => {
-                let r: String = init();
-                flow(zuu(r.as_str()), r.as_str());
+                if let Some(r) = init() {
+                    flow(zuu(r.as_str()), r.as_str());
+                }
             }
             GENERATE_README => {
                 assert!(generate_readme(r));
             }
             CHECK_README_WORDS => {
-                assert!(verify_readme_part(r));
+                let _ = verify_readme_part(r);
             }
             DISPLAY_README => {
                 assert!(print_readme(r));
@@ -1238,7 +2183,10 @@ fn flow(z: bool, r: &str) {
                 assert!(clone());
             }
             SHOW_LOGS => {
-                assert!(logs(r));
+                assert!(logs(r, None));
+            }
+            GPG_KEY => {
+                assert!(display_signing(r));
             }
             SHOW_DIFF => {
                 assert!(diff(r));
@@ -1259,7 +2207,7 @@ fn flow(z: bool, r: &str) {
                 assert!(remove_tags(r));
             }
             GENERATE_CHANGE_LOG => {
-                assert!(create_changelog(r));
+                assert!(create_changelog(r, None));
             }
             _ => {
                 unreachable!();
@@ -1304,18 +2252,122 @@ fn open(path: &str) -> Repository {
     Repository::open(path).expect("Not a git repository")
 }
 
-fn logs(path: &str) -> bool {
+fn logs(path: &str, style: Option<Style>) -> bool {
+    let Some(translator) = translator(path) else {
+        return false;
+    };
     let repo: Repository = open(path);
     let mut revwalk: Revwalk<'_> = repo.revwalk().expect("msg"); // Create a Revwalk object to iterate through commits
     revwalk.push_head().expect("msg"); // Start from the HEAD commit
 
     for (_i, oid) in revwalk.enumerate().take(50) {
         let commit: Commit<'_> = repo.find_commit(oid.expect("msg")).expect("msg");
-        let message = commit.message().unwrap_or("No commit message");
-        println!("\n{message}\n");
+        match CommitMessage::from_commit(&commit) {
+            Some(mut m) => {
+                if let Some(style) = style {
+                    translator.translate(&mut m, style);
+                }
+                print_message(&commit, &m);
+            }
+            None => {
+                let message = commit.message().unwrap_or("No commit message");
+                println!("\n{} {}", short_id(&commit).yellow(), message.trim());
+            }
+        }
     }
     true
 }
+
+///
+/// Check the messages of the commits of `range` with the lint rules, the unpushed commits when it is not given.
+///
+fn lint_commits(r: &str, range: Option<&str>) -> bool {
+    let repo: Repository = open(r);
+    let commits: Vec<git2::Oid> = match range.map_or_else(
+        || rewrite::unpushed(&repo),
+        |range| rewrite::range(&repo, range),
+    ) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{e}");
+            return false;
+        }
+    };
+    let Some(linter) = linter(r) else {
+        return false;
+    };
+    let (mut errors, mut warnings): (usize, usize) = (0, 0);
+    for commit in commits.iter().filter_map(|id| repo.find_commit(*id).ok()) {
+        let text: &str = commit.message().unwrap_or_default();
+        if lint::generated(text) {
+            continue;
+        }
+        let violations: Vec<Violation> = linter.lint(text);
+        if violations.is_empty() {
+            continue;
+        }
+        println!(
+            "\n{} {}",
+            short_id(&commit).yellow(),
+            rewrite::subject(&commit)
+        );
+        for v in &violations {
+            if v.severity.eq(&lint::Severity::Error) {
+                errors += 1;
+                println!("    {}", v.to_string().red());
+            } else {
+                warnings += 1;
+                println!("    {}", v.to_string().yellow());
+            }
+        }
+    }
+    println!(
+        "\n{} commits checked, {errors} errors, {warnings} warnings",
+        commits.len()
+    );
+    errors.eq(&0)
+}
+
+fn short_id(commit: &Commit<'_>) -> String {
+    commit.id().to_string().chars().take(7).collect()
+}
+
+fn print_message(commit: &Commit<'_>, m: &CommitMessage) {
+    let scope: String = if m.scope.is_empty() {
+        String::new()
+    } else {
+        format!("({})", m.scope)
+    };
+    println!(
+        "\n{} {}{}: {}",
+        short_id(commit).yellow(),
+        m.commit_type.green().bold(),
+        scope.cyan(),
+        m.summary
+    );
+    for w in &m.why {
+        println!("    * {w}");
+    }
+    for b in &m.breaking {
+        println!("    {} {b}", "BREAKING".red().bold());
+    }
+    for i in &m.fixes {
+        println!("    Fixes #{i}");
+    }
+    for i in &m.closes {
+        println!("    Closes #{i}");
+    }
+    println!(
+        "    {} <{}> the {}",
+        m.author.blue(),
+        m.email,
+        m.date.as_str().dimmed()
+    );
+}
+
+///
+/// Create the readme parts, the logs directory and the README.md of `r` when they are missing.
+///
 fn zazen_check(r: &str) {
     if Path::new(format!("{r}{MAIN_SEPARATOR_STR}zazen").as_str())
         .is_dir()
@@ -1366,13 +2418,95 @@ fn zazen_check(r: &str) {
     }
 }
 
-fn init() -> String {
+fn init() -> Option<String> {
     let r: String = repo();
-    zazen_check(r.as_str());
-    assert!(set_current_dir(r.as_str()).is_ok());
-    r
+    if enter(r.as_str()).eq(&false) {
+        return None;
+    }
+    zazen_check(".");
+    Some(r)
 }
-fn main() {
-    let r: String = init();
-    flow(zuu(r.as_str()), r.as_str());
+
+///
+/// Move to the repository `r`, the error being printed when it cannot be entered.
+///
+fn enter(r: &str) -> bool {
+    set_current_dir(r)
+        .map_err(|e| eprintln!("cannot enter {r} : {e}"))
+        .is_ok()
+}
+
+fn run(action: Action, r: &str) -> bool {
+    if let Action::Clone { url } = &action {
+        return clone_url(url);
+    }
+    if let Action::Hook(h) = &action {
+        return match h {
+            HookAction::Install => hook::install(r),
+            HookAction::Uninstall => hook::uninstall(r),
+            HookAction::Status => hook::status(r),
+            HookAction::CommitMsg { file } => hook::commit_msg(r, file),
+        };
+    }
+    if enter(r).eq(&false) {
+        return false;
+    }
+    let r: &str = ".";
+    if matches!(action, Action::Readme(_)) {
+        zazen_check(r);
+    }
+    match action {
+        Action::Commit(args) => commit(r, &args),
+        Action::Amend(args) => amend(r, &args),
+        Action::Fixup(args) => fixup(r, &args),
+        Action::Autosquash => autosquash(r),
+        Action::Types => display_commit_types(r),
+        Action::Templates => check_templates(r),
+        Action::Changelog { style, format } => match format {
+            Format::Announcement => create_changelog(r, style),
+            Format::KeepAChangelog => keep_changelog(r, style),
+            Format::Json | Format::Html | Format::Atom => export_changelog(r, style, format),
+        },
+        Action::Readme(ReadmeAction::Generate) => generate_readme(r),
+        Action::Readme(ReadmeAction::Check) => verify_readme_part(r),
+        Action::Readme(ReadmeAction::Display) => print_readme(r),
+        Action::Branches(RefAction::List) => display_branches(r),
+        Action::Branches(RefAction::Rm { names }) if names.is_empty() => remove_branches(r),
+        Action::Branches(RefAction::Rm { names }) => remove_all(&names, r, remove_branch),
+        Action::Tags(RefAction::List) => display_tags(r),
+        Action::Tags(RefAction::Rm { names }) if names.is_empty() => remove_tags(r),
+        Action::Tags(RefAction::Rm { names }) => remove_all(&names, r, remove_tag),
+        Action::Status => display_status(r),
+        Action::Diff => diff(r),
+        Action::Unstage { files } => unstage(r, &files),
+        Action::Signing => display_signing(r),
+        Action::Lint { range } => lint_commits(r, range.as_deref()),
+        Action::Log { style } => logs(r, style),
+        Action::Push => send(r),
+        Action::Open => code(r),
+        Action::Clone { .. } | Action::Hook(_) => unreachable!(),
+    }
+}
+
+fn main() -> ExitCode {
+    let cli: Cli = Cli::parse();
+    if let Some(p) = cli.package.as_deref() {
+        workspace::select(p);
+    }
+    match cli.command {
+        Some(action) => {
+            if run(action, cli.repo.as_str()) {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        None => {
+            let Some(r) = init() else {
+                return ExitCode::FAILURE;
+            };
+            flow(zuu(r.as_str()), r.as_str());
+            ExitCode::SUCCESS
+        }
+    }
 }
@@ -0,0 +1,458 @@
+use crate::template::Value;
+use crate::trailers::{self, BREAKING_CHANGE, CO_AUTHORED_BY};
+use chrono::DateTime;
+use git2::Commit;
+use serde::{Deserialize, Serialize};
+
+/// The maximum length of a scope.
+pub const SCOPE_LENGTH: usize = 20;
+/// The maximum length of a summary.
+pub const SUMMARY_LENGTH: usize = 50;
+/// The maximum length of a reasoning behind the change.
+pub const WHY_LENGTH: usize = 50;
+
+const WHY: &str = "the following changes were made";
+const BREAK: &str = "the following changes break";
+const FIXES: &str = "the commit resolve their issues";
+const CLOSES: &str = "the commit close their issues";
+const AUTHORED: &str = "authored by";
+const FIXES_KEYWORDS: [&str; 6] = ["fixes", "fix", "fixed", "resolves", "resolve", "resolved"];
+const CLOSES_KEYWORDS: [&str; 3] = ["closes", "close", "closed"];
+const BREAKING_KEYWORDS: [&str; 2] = ["BREAKING CHANGE", "BREAKING-CHANGE"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Body,
+    Why,
+    Breaking,
+    Issues,
+    Author,
+}
+
+///
+/// The parts of a commit message.
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitMessage {
+    pub commit_type: String,
+    pub scope: String,
+    pub summary: String,
+    pub why: Vec<String>,
+    pub breaking: Vec<String>,
+    pub fixes: Vec<String>,
+    pub closes: Vec<String>,
+    pub trailers: Vec<(String, String)>,
+    pub author: String,
+    pub email: String,
+    pub date: String,
+}
+
+impl CommitMessage {
+    ///
+    /// Parse a message written by zazen, or following the Conventional Commits.
+    ///
+    /// The header must look like `type(scope): summary`, the scope and a `!` before the colon being optional. Each
+    /// bullet and each paragraph of the body outside the zazen sections is taken as a reason, `Fixes #1` and
+    /// `Closes #2` are found on any line and `BREAKING CHANGE: description` footers are taken as breaking changes.
+    /// The last paragraph is taken as trailers when each of its lines is a `Key: value` trailer or an issue line like
+    /// `Refs #3`.
+    ///
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let lines: Vec<&str> = text
+            .lines()
+            .filter(|l| l.starts_with('#').eq(&false))
+            .skip_while(|l| l.trim().is_empty())
+            .collect();
+        let (commit_type, scope, bang, summary) = header(lines.first()?.trim())?;
+        let mut m: Self = Self {
+            commit_type,
+            scope,
+            summary,
+            ..Self::default()
+        };
+        let (body, footer) = split_trailers(&lines[1..]);
+        for line in footer {
+            if let Some((key, value)) = trailers::parse(line) {
+                m.trailer(key, value);
+            }
+        }
+        let mut section: Section = Section::Body;
+        let mut paragraph: bool = false;
+        for line in body {
+            let l: &str = line.trim();
+            if l.is_empty() {
+                paragraph = false;
+                continue;
+            }
+            let continued: bool = paragraph && l.starts_with(['*', '-']).eq(&false);
+            paragraph = false;
+            if let Some(s) = heading(l) {
+                section = s;
+                continue;
+            }
+            if let Some(issue) = reference(l, &FIXES_KEYWORDS) {
+                m.fixes.push(issue);
+                continue;
+            }
+            if let Some(issue) = reference(l, &CLOSES_KEYWORDS) {
+                m.closes.push(issue);
+                continue;
+            }
+            if let Some(b) = BREAKING_KEYWORDS
+                .iter()
+                .find_map(|k| l.strip_prefix(k)?.strip_prefix(':'))
+            {
+                m.breaking.push(b.trim().to_string());
+                continue;
+            }
+            let list: &mut Vec<String> = match section {
+                Section::Body | Section::Why => &mut m.why,
+                Section::Breaking => &mut m.breaking,
+                Section::Author => {
+                    m.authored(bullet(l).as_str());
+                    continue;
+                }
+                Section::Issues => continue,
+            };
+            match list.last_mut() {
+                Some(last) if continued => {
+                    last.push(' ');
+                    last.push_str(l);
+                }
+                _ => list.push(bullet(l)),
+            }
+            paragraph = true;
+        }
+        if bang && m.breaking.is_empty() {
+            m.breaking.push(m.summary.clone());
+        }
+        Some(m)
+    }
+
+    ///
+    /// Parse the message of a commit, the author and the date of the commit are used when the message has none.
+    ///
+    #[must_use]
+    pub fn from_commit(commit: &Commit<'_>) -> Option<Self> {
+        let mut m: Self = Self::parse(commit.message().unwrap_or_default())?;
+        if m.author.is_empty() {
+            let author = commit.author();
+            m.author = author.name().unwrap_or_default().to_string();
+            m.email = author.email().unwrap_or_default().to_string();
+        }
+        if m.date.is_empty() {
+            m.date = DateTime::from_timestamp(commit.time().seconds(), 0)
+                .map(|d| d.date_naive().to_string())
+                .unwrap_or_default();
+        }
+        Some(m)
+    }
+
+    fn trailer(&mut self, key: String, value: String) {
+        let issue: &str = value.trim_start_matches('#');
+        let numbered: bool =
+            issue.is_empty().eq(&false) && issue.chars().all(|c| c.is_ascii_digit());
+        if numbered && FIXES_KEYWORDS.contains(&key.to_lowercase().as_str()) {
+            self.fixes.push(issue.to_string());
+        } else if numbered && CLOSES_KEYWORDS.contains(&key.to_lowercase().as_str()) {
+            self.closes.push(issue.to_string());
+        } else if key.eq(BREAKING_CHANGE) {
+            self.breaking.push(value);
+        } else {
+            self.trailers.push((key, value));
+        }
+    }
+
+    ///
+    /// The `Key: value` lines of the footer, the issues first.
+    ///
+    #[must_use]
+    pub fn footer(&self) -> Vec<String> {
+        self.fixes
+            .iter()
+            .map(|f| format!("{}: #{f}", trailers::FIXES))
+            .chain(
+                self.closes
+                    .iter()
+                    .map(|c| format!("{}: #{c}", trailers::CLOSES)),
+            )
+            .chain(self.trailers.iter().map(|(k, v)| format!("{k}: {v}")))
+            .collect()
+    }
+
+    ///
+    /// The free text of the footer, the breaking changes then the values of the trailers not naming a person or an
+    /// issue.
+    ///
+    #[must_use]
+    pub fn footer_prose(&self) -> Vec<String> {
+        self.breaking
+            .iter()
+            .cloned()
+            .chain(
+                self.trailers
+                    .iter()
+                    .filter(|(k, _)| trailers::is_prose(k))
+                    .map(|(_, v)| v.clone()),
+            )
+            .collect()
+    }
+
+    fn authored(&mut self, line: &str) {
+        let (name, rest) = line.split_once('<').unwrap_or((line, ""));
+        let (email, date) = rest.split_once('>').unwrap_or((rest, ""));
+        self.author = name.trim().to_string();
+        self.email = email.trim().to_string();
+        self.date = date
+            .trim()
+            .trim_start_matches("the")
+            .trim_start()
+            .to_string();
+    }
+
+    ///
+    /// The template context of the message.
+    ///
+    #[must_use]
+    pub fn context(&self) -> Value {
+        Value::from([
+            ("type", Value::from(self.commit_type.as_str())),
+            ("scope", Value::from(self.scope.as_str())),
+            ("summary", Value::from(self.summary.as_str())),
+            ("why", Value::from(self.why.as_slice())),
+            ("breaking", Value::from(self.breaking.as_slice())),
+            ("fixes", Value::from(self.fixes.as_slice())),
+            ("closes", Value::from(self.closes.as_slice())),
+            ("trailers", Value::from(self.footer().as_slice())),
+            ("author", Value::from(self.author.as_str())),
+            ("email", Value::from(self.email.as_str())),
+            ("date", Value::from(self.date.as_str())),
+        ])
+    }
+
+    ///
+    /// A message having every part, used to check the templates.
+    ///
+    #[must_use]
+    pub fn sample() -> Self {
+        let one = |x: &str| vec![x.to_string()];
+        Self {
+            commit_type: String::from("Star"),
+            scope: String::from("scope"),
+            summary: String::from("summary"),
+            why: one("why"),
+            breaking: one("breaking"),
+            fixes: one("1"),
+            closes: one("2"),
+            trailers: vec![(String::from(CO_AUTHORED_BY), String::from("author <email>"))],
+            author: String::from("author"),
+            email: String::from("email"),
+            date: String::from("date"),
+        }
+    }
+}
+
+fn header(line: &str) -> Option<(String, String, bool, String)> {
+    let (left, summary) = line.split_once(':')?;
+    let summary: &str = summary.trim();
+    let (left, bang) = left.strip_suffix('!').map_or((left, false), |l| (l, true));
+    let (commit_type, scope) = match left.split_once('(') {
+        Some((t, s)) => (t, s.strip_suffix(')')?),
+        None => (left, ""),
+    };
+    let valid = commit_type.starts_with(|c: char| c.is_alphabetic())
+        && commit_type
+            .chars()
+            .all(|c| c.is_alphanumeric() || c.eq(&' ') || c.eq(&'-') || c.eq(&'_'))
+        && commit_type.len().le(&40)
+        && scope.contains(['(', ')']).eq(&false)
+        && summary.is_empty().eq(&false);
+    if valid {
+        Some((
+            commit_type.trim().to_string(),
+            scope.trim().to_string(),
+            bang,
+            summary.to_string(),
+        ))
+    } else {
+        None
+    }
+}
+
+///
+/// Split the lines after the header into the body and the trailers of the last paragraph.
+///
+fn split_trailers<'a>(lines: &'a [&'a str]) -> (&'a [&'a str], &'a [&'a str]) {
+    let end: usize = lines
+        .iter()
+        .rposition(|l| l.trim().is_empty().eq(&false))
+        .map_or(0, |x| x + 1);
+    let start: usize = lines[..end]
+        .iter()
+        .rposition(|l| l.trim().is_empty())
+        .map_or(0, |x| x + 1);
+    if start.gt(&0) && trailers::is_block(&lines[start..end]) {
+        (&lines[..start], &lines[start..end])
+    } else {
+        (lines, &[])
+    }
+}
+
+fn heading(line: &str) -> Option<Section> {
+    let h: String = line.trim_end_matches(':').trim().to_lowercase();
+    match h.as_str() {
+        WHY => Some(Section::Why),
+        BREAK => Some(Section::Breaking),
+        FIXES | CLOSES => Some(Section::Issues),
+        AUTHORED => Some(Section::Author),
+        _ => None,
+    }
+}
+
+fn reference(line: &str, keywords: &[&str]) -> Option<String> {
+    let (keyword, issue) = line.split_once(' ')?;
+    let keyword: String = keyword.trim_end_matches(':').to_lowercase();
+    let issue: &str = issue.trim();
+    if keywords.contains(&keyword.as_str())
+        && issue.starts_with('#')
+        && issue.len().gt(&1)
+        && issue[1..].chars().all(|c| c.is_ascii_digit())
+    {
+        Some(issue.trim_start_matches('#').to_string())
+    } else {
+        None
+    }
+}
+
+fn bullet(line: &str) -> String {
+    line.trim_start_matches(['*', '-']).trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommitMessage;
+    use crate::template::Template;
+
+    fn render(template: &str, m: &CommitMessage) -> String {
+        Template::parse(template)
+            .expect("valid template")
+            .render(&m.context())
+            .expect("rendered")
+    }
+
+    fn parts(m: &CommitMessage) -> Vec<String> {
+        vec![
+            m.commit_type.clone(),
+            m.scope.clone(),
+            m.summary.clone(),
+            m.why.join("|"),
+            m.breaking.join("|"),
+            m.fixes.join("|"),
+            m.closes.join("|"),
+            format!("{:?}", m.trailers),
+        ]
+    }
+
+    fn message() -> CommitMessage {
+        CommitMessage {
+            commit_type: String::from("Star"),
+            scope: String::from("cli"),
+            summary: String::from("add the lint command"),
+            why: vec![String::from("check the history"), String::from("see PR #3")],
+            breaking: vec![String::from("remove --check")],
+            fixes: vec![String::from("12")],
+            closes: vec![String::from("13")],
+            trailers: vec![
+                (
+                    String::from("Co-authored-by"),
+                    String::from("Ann <ann@example.com>"),
+                ),
+                (String::from("Refs"), String::from("#14")),
+            ],
+            author: String::from("Bob"),
+            email: String::from("bob@example.com"),
+            date: String::from("2024-01-31"),
+        }
+    }
+
+    #[test]
+    fn the_cosmic_message_round_trips() {
+        let m: CommitMessage = message();
+        let parsed: CommitMessage =
+            CommitMessage::parse(render(include_str!("commit-template"), &m).as_str())
+                .expect("valid message");
+        assert_eq!(parts(&parsed), parts(&m));
+        assert_eq!(parsed.author, m.author);
+        assert_eq!(parsed.email, m.email);
+        assert_eq!(parsed.date, m.date);
+    }
+
+    #[test]
+    fn the_conventional_message_round_trips() {
+        let mut m: CommitMessage = message();
+        m.commit_type = String::from("feat");
+        let parsed: CommitMessage =
+            CommitMessage::parse(render(include_str!("conventional-template"), &m).as_str())
+                .expect("valid message");
+        assert_eq!(parts(&parsed), parts(&m));
+        m.why.clear();
+        m.breaking.clear();
+        m.fixes.clear();
+        m.trailers.clear();
+        let parsed: CommitMessage =
+            CommitMessage::parse(render(include_str!("conventional-template"), &m).as_str())
+                .expect("valid message");
+        assert_eq!(parts(&parsed), parts(&m));
+    }
+
+    #[test]
+    fn a_last_paragraph_of_prose_stays_in_the_body() {
+        let m: CommitMessage =
+            CommitMessage::parse("fix(cli): keep the flags\n\nsee PR #3\n").expect("valid message");
+        assert_eq!(m.why, vec!["see PR #3"]);
+        assert!(m.trailers.is_empty());
+        let m: CommitMessage =
+            CommitMessage::parse("fix: keep the flags\n\nRefs #3\n").expect("valid message");
+        assert!(m.why.is_empty());
+        assert_eq!(m.trailers, vec![(String::from("Refs"), String::from("#3"))]);
+    }
+
+    #[test]
+    fn paragraphs_and_bullets_are_reasons() {
+        let m: CommitMessage = CommitMessage::parse(
+            "docs: explain\n\nThe readme was\nwrapped at 72 columns.\n\n- first\n  reason\n- second\n\nFixes #4\n",
+        )
+        .expect("valid message");
+        assert_eq!(
+            m.why,
+            vec![
+                "The readme was wrapped at 72 columns.",
+                "first reason",
+                "second"
+            ]
+        );
+        assert_eq!(m.fixes, vec!["4"]);
+    }
+
+    #[test]
+    fn the_footer_prose_skips_the_people_and_the_issues() {
+        let mut m: CommitMessage = message();
+        m.trailers
+            .push((String::from("Note"), String::from("run the migration")));
+        assert_eq!(
+            m.footer_prose(),
+            vec!["remove --check", "run the migration"]
+        );
+    }
+
+    #[test]
+    fn the_header_is_required() {
+        assert!(CommitMessage::parse("no header here").is_none());
+        assert!(CommitMessage::parse("fix(: broken").is_none());
+        let m: CommitMessage =
+            CommitMessage::parse("# comment\n\nfeat!: drop the v1 api\n").expect("valid message");
+        assert_eq!(m.breaking, vec!["drop the v1 api"]);
+    }
+}
@@ -0,0 +1,469 @@
+use crate::message::CommitMessage;
+use crate::signing;
+use crate::template::Template;
+use git2::{Branch, Commit, Index, Oid, Reference, Repository, Revwalk, Signature, Sort, Tree};
+
+/// The subject prefix of a commit melted into its target by the autosquash.
+pub const FIXUP: &str = "fixup! ";
+/// The subject prefix of a commit whose reasons are added to its target by the autosquash.
+pub const SQUASH: &str = "squash! ";
+
+///
+/// The commits of the current branch not reachable from its upstream, the oldest first.
+///
+/// # Errors
+///
+/// If HEAD is not on a branch, the branch has no upstream or the history cannot be walked.
+///
+pub fn unpushed(repo: &Repository) -> Result<Vec<Oid>, git2::Error> {
+    let head: Reference<'_> = repo.head()?;
+    if head.is_branch().eq(&false) {
+        return Err(git2::Error::from_str(
+            "HEAD is not on a branch, the unpushed commits are unknown",
+        ));
+    }
+    let branch: Branch<'_> = Branch::wrap(head);
+    let name: String = branch.name()?.unwrap_or_default().to_string();
+    let upstream: Oid = branch
+        .upstream()
+        .ok()
+        .and_then(|u| u.get().target())
+        .ok_or_else(|| {
+            git2::Error::from_str(
+                format!("{name} has no upstream, set it with git push -u or git branch -u")
+                    .as_str(),
+            )
+        })?;
+    let mut revwalk: Revwalk<'_> = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    revwalk.push_head()?;
+    revwalk.hide(upstream)?;
+    revwalk.collect()
+}
+
+///
+/// The commits of `spec`, the oldest first. `spec` is a range like `HEAD~20..HEAD`, a symmetric range like
+/// `main...HEAD` giving the commits of either side not in the other, or a single revision.
+///
+/// # Errors
+///
+/// If the revisions cannot be resolved or the history cannot be walked.
+///
+pub fn range(repo: &Repository, spec: &str) -> Result<Vec<Oid>, git2::Error> {
+    if spec.contains("..").eq(&false) {
+        return Ok(vec![repo.revparse_single(spec)?.peel_to_commit()?.id()]);
+    }
+    let mut revwalk: Revwalk<'_> = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    if let Some((from, to)) = spec.split_once("...") {
+        let side = |r: &str| -> Result<Oid, git2::Error> {
+            Ok(repo
+                .revparse_single(if r.is_empty() { "HEAD" } else { r })?
+                .peel_to_commit()?
+                .id())
+        };
+        let (from, to): (Oid, Oid) = (side(from)?, side(to)?);
+        revwalk.push(from)?;
+        revwalk.push(to)?;
+        if let Ok(bases) = repo.merge_bases(from, to) {
+            for base in bases.iter() {
+                revwalk.hide(*base)?;
+            }
+        }
+    } else {
+        revwalk.push_range(spec)?;
+    }
+    revwalk.collect()
+}
+
+///
+/// The first line of the message of `commit`.
+///
+#[must_use]
+pub fn subject(commit: &Commit<'_>) -> String {
+    commit.summary().unwrap_or_default().to_string()
+}
+
+///
+/// The prefix and the target subject of a fixup or a squash commit.
+///
+fn target(commit: &Commit<'_>) -> Option<(&'static str, String)> {
+    let subject: String = subject(commit);
+    [FIXUP, SQUASH].into_iter().find_map(|p| {
+        subject.strip_prefix(p).map(|t| {
+            (
+                p,
+                t.trim_start_matches(FIXUP)
+                    .trim_start_matches(SQUASH)
+                    .to_string(),
+            )
+        })
+    })
+}
+
+enum Step {
+    Pick(Oid),
+    Meld(Oid, &'static str),
+}
+
+///
+/// The steps of the autosquash of `commits`, each fixup or squash commit moved after its target, the latest commit
+/// before it whose subject starts with the target subject or whose id starts with it. A fixup or squash commit without
+/// a target subject is kept where it is.
+///
+fn plan(repo: &Repository, commits: &[Oid]) -> Result<(Vec<Step>, usize), git2::Error> {
+    let mut melds: Vec<Option<(&'static str, Oid)>> = Vec::new();
+    for (i, oid) in commits.iter().enumerate() {
+        let commit: Commit<'_> = repo.find_commit(*oid)?;
+        let found: Option<(&'static str, Oid)> = match target(&commit) {
+            Some((prefix, t)) if t.trim().is_empty().eq(&false) => commits[..i]
+                .iter()
+                .rev()
+                .find(|o| {
+                    repo.find_commit(**o).is_ok_and(|c| {
+                        let s: String = subject(&c);
+                        (target(&c).is_none() && (s.eq(&t) || s.starts_with(t.as_str())))
+                            || (o.to_string().starts_with(t.as_str()) && t.len().ge(&7))
+                    })
+                })
+                .map(|o| (prefix, *o)),
+            _ => None,
+        };
+        melds.push(found);
+    }
+    let mut steps: Vec<Step> = Vec::new();
+    for (i, oid) in commits.iter().enumerate() {
+        if melds[i].is_some() {
+            continue;
+        }
+        steps.push(Step::Pick(*oid));
+        for (j, m) in melds.iter().enumerate() {
+            if let Some((prefix, t)) = m {
+                if t.eq(oid) {
+                    steps.push(Step::Meld(commits[j], prefix));
+                }
+            }
+        }
+    }
+    Ok((steps, melds.iter().flatten().count()))
+}
+
+fn squash_message(target: &Commit<'_>, squash: &Commit<'_>, template: &Template) -> String {
+    let reasons: Vec<String> = squash
+        .message()
+        .unwrap_or_default()
+        .lines()
+        .skip(1)
+        .map(|l| l.trim().trim_start_matches(['*', '-']).trim().to_string())
+        .filter(|l| l.is_empty().eq(&false))
+        .collect();
+    let message: String = target.message().unwrap_or_default().to_string();
+    if reasons.is_empty() {
+        return message;
+    }
+    match CommitMessage::from_commit(target) {
+        Some(mut m) => {
+            m.why.extend(reasons);
+            template.render(&m.context()).unwrap_or(message)
+        }
+        None => format!("{}\n\n{}\n", message.trim_end(), reasons.join("\n")),
+    }
+}
+
+fn replay<'r>(
+    repo: &'r Repository,
+    commit: &Commit<'_>,
+    onto: &Commit<'_>,
+) -> Result<Tree<'r>, String> {
+    let mut index: Index = repo
+        .cherrypick_commit(commit, onto, 0, None)
+        .map_err(|e| format!("failed to apply {} : {}", commit.id(), e.message()))?;
+    if index.has_conflicts() {
+        return Err(format!(
+            "{} {} conflicts with the commits before it, nothing has been changed",
+            &commit.id().to_string()[..7],
+            subject(commit)
+        ));
+    }
+    index
+        .write_tree_to(repo)
+        .and_then(|t| repo.find_tree(t))
+        .map_err(|e| format!("failed to write the tree : {}", e.message()))
+}
+
+///
+/// Display the commits of the autosquash of the unpushed commits, each line being the commit and its action.
+///
+/// # Errors
+///
+/// If the history cannot be read.
+///
+pub fn preview(repo: &Repository) -> Result<Vec<String>, String> {
+    let commits: Vec<Oid> = unpushed(repo).map_err(|e| e.message().to_string())?;
+    let (steps, _) = plan(repo, &commits).map_err(|e| e.message().to_string())?;
+    let line = |oid: &Oid, action: &str| {
+        repo.find_commit(*oid).map_or_else(
+            |_| oid.to_string(),
+            |c| format!("{action:<6} {} {}", &oid.to_string()[..7], subject(&c)),
+        )
+    };
+    Ok(steps
+        .iter()
+        .map(|s| match s {
+            Step::Pick(o) => line(o, "pick"),
+            Step::Meld(o, p) => line(o, p.trim_end_matches("! ")),
+        })
+        .collect())
+}
+
+///
+/// Melt the fixup and squash commits of the unpushed history into their target, return the number of commits melted.
+///
+/// The reasons of a squash commit are added to the reasons of its target, rendered with `template`. The branch is
+/// moved only when the new history gives the tree of HEAD, the index and the working tree are kept.
+///
+/// # Errors
+///
+/// If a commit is a merge, a fixup conflicts, or a commit cannot be written or signed.
+///
+pub fn autosquash(repo: &Repository, template: &Template) -> Result<usize, String> {
+    let commits: Vec<Oid> = unpushed(repo).map_err(|e| e.message().to_string())?;
+    let (steps, count) = plan(repo, &commits).map_err(|e| e.message().to_string())?;
+    if count.eq(&0) {
+        return Ok(0);
+    }
+    let find = |o: &Oid| {
+        repo.find_commit(*o)
+            .map_err(|e| format!("failed to read {o} : {}", e.message()))
+    };
+    let committer: Signature<'_> = signing::committer(repo)?;
+    let mut tip: Option<Commit<'_>> = None;
+    let mut rewritten: bool = false;
+    for step in &steps {
+        let (oid, prefix) = match step {
+            Step::Pick(o) => (o, None),
+            Step::Meld(o, p) => (o, Some(*p)),
+        };
+        let commit: Commit<'_> = find(oid)?;
+        if commit.parent_count().gt(&1) {
+            return Err(format!(
+                "{} {} is a merge, the history cannot be rewritten",
+                &oid.to_string()[..7],
+                subject(&commit)
+            ));
+        }
+        let next: Oid = match (prefix, &tip) {
+            (None, None) => commit.id(),
+            (None, Some(t))
+                if rewritten.eq(&false) && commit.parent_id(0).is_ok_and(|p| p.eq(&t.id())) =>
+            {
+                commit.id()
+            }
+            (None, Some(t)) => {
+                rewritten = true;
+                let tree: Tree<'_> = replay(repo, &commit, t)?;
+                signing::write(
+                    repo,
+                    &commit.author(),
+                    &committer,
+                    commit.message().unwrap_or_default(),
+                    &tree,
+                    &[t],
+                )?
+            }
+            (Some(p), Some(t)) => {
+                rewritten = true;
+                let tree: Tree<'_> = replay(repo, &commit, t)?;
+                let message: String = if p.eq(SQUASH) {
+                    squash_message(t, &commit, template)
+                } else {
+                    t.message().unwrap_or_default().to_string()
+                };
+                let parents: Vec<Commit<'_>> = t.parents().collect();
+                let parents: Vec<&Commit<'_>> = parents.iter().collect();
+                signing::write(
+                    repo,
+                    &t.author(),
+                    &committer,
+                    message.as_str(),
+                    &tree,
+                    &parents,
+                )?
+            }
+            (Some(_), None) => unreachable!("a fixup always follows its target"),
+        };
+        tip = Some(find(&next)?);
+    }
+    let tip: Commit<'_> = tip.expect("the plan has commits");
+    let head: Commit<'_> = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| e.message().to_string())?;
+    if tip.tree_id().ne(&head.tree_id()) {
+        return Err(String::from(
+            "the squashed history does not give the content of HEAD, nothing has been changed",
+        ));
+    }
+    signing::update_head(repo, tip.id(), "autosquash", subject(&tip).as_str())?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    ///
+    /// Commit `subject` on `branch` over `parents`, with an empty tree.
+    ///
+    fn commit(repo: &Repository, branch: &str, subject: &str, parents: &[Oid]) -> Oid {
+        let signature: Signature<'_> = Signature::now("Ann", "ann@example.com").expect("signature");
+        let tree: Tree<'_> = repo
+            .find_tree(repo.index().and_then(|mut i| i.write_tree()).expect("tree"))
+            .expect("tree");
+        let parents: Vec<Commit<'_>> = parents
+            .iter()
+            .map(|p| repo.find_commit(*p).expect("parent"))
+            .collect();
+        repo.commit(
+            Some(format!("refs/heads/{branch}").as_str()),
+            &signature,
+            &signature,
+            subject,
+            &tree,
+            &parents.iter().collect::<Vec<&Commit<'_>>>(),
+        )
+        .expect("commit")
+    }
+
+    #[test]
+    fn range_accepts_two_and_three_dots() {
+        let dir: TempDir = tempfile::tempdir().expect("temporary directory");
+        let repo: Repository = Repository::init(dir.path()).expect("repository");
+        let base: Oid = commit(&repo, "main", "base", &[]);
+        let main: Oid = commit(&repo, "main", "main", &[base]);
+        let first: Oid = commit(&repo, "topic", "first", &[base]);
+        let second: Oid = commit(&repo, "topic", "second", &[first]);
+        assert_eq!(
+            range(&repo, "main..topic").expect("range"),
+            vec![first, second]
+        );
+        let mut symmetric: Vec<Oid> = range(&repo, "main...topic").expect("range");
+        symmetric.sort();
+        let mut expected: Vec<Oid> = vec![main, first, second];
+        expected.sort();
+        assert_eq!(symmetric, expected);
+        assert_eq!(range(&repo, "topic~1").expect("range"), vec![first]);
+    }
+
+    fn steps(repo: &Repository, commits: &[Oid]) -> (Vec<String>, usize) {
+        let (steps, count) = plan(repo, commits).expect("plan");
+        let name = |o: &Oid| subject(&repo.find_commit(*o).expect("commit"));
+        (
+            steps
+                .iter()
+                .map(|s| match s {
+                    Step::Pick(o) => format!("pick {}", name(o)),
+                    Step::Meld(o, p) => format!("{}{}", p, name(o)),
+                })
+                .collect(),
+            count,
+        )
+    }
+
+    #[test]
+    fn plan_moves_each_fixup_after_its_target() {
+        let dir: TempDir = tempfile::tempdir().expect("temporary directory");
+        let repo: Repository = Repository::init(dir.path()).expect("repository");
+        let a: Oid = commit(&repo, "main", "Star(cli): add the flags", &[]);
+        let b: Oid = commit(&repo, "main", "Comet(cli): fix the help", &[a]);
+        let short: String = a.to_string()[..7].to_string();
+        let mut commits: Vec<Oid> = vec![a, b];
+        for subject in [
+            "fixup! Star(cli): add the flags",
+            "squash! Comet(cli)",
+            "fixup! ",
+            format!("fixup! {short}").as_str(),
+            "fixup! fixup! Star(cli): add the flags",
+            "fixup! Nebula(docs): unknown",
+        ] {
+            let parent: Oid = *commits.last().expect("parent");
+            commits.push(commit(&repo, "main", subject, &[parent]));
+        }
+        assert_eq!(
+            steps(&repo, &commits),
+            (
+                vec![
+                    String::from("pick Star(cli): add the flags"),
+                    String::from("fixup! fixup! Star(cli): add the flags"),
+                    format!("fixup! fixup! {short}"),
+                    String::from("fixup! fixup! fixup! Star(cli): add the flags"),
+                    String::from("pick Comet(cli): fix the help"),
+                    String::from("squash! squash! Comet(cli)"),
+                    String::from("pick fixup!"),
+                    String::from("pick fixup! Nebula(docs): unknown"),
+                ],
+                4
+            )
+        );
+    }
+
+    #[test]
+    fn squash_message_adds_the_reasons_to_the_target() {
+        let dir: TempDir = tempfile::tempdir().expect("temporary directory");
+        let repo: Repository = Repository::init(dir.path()).expect("repository");
+        let template: Template =
+            Template::parse(include_str!("commit-template")).expect("valid template");
+        let mut m: CommitMessage = CommitMessage::sample();
+        m.why = vec![String::from("keep the flags")];
+        let message: String = template.render(&m.context()).expect("message");
+        let target: Oid = commit(&repo, "main", message.as_str(), &[]);
+        let squash: Oid = commit(
+            &repo,
+            "main",
+            "squash! Star(scope): summary\n\n* drop the cache\n- sort the flags\n",
+            &[target],
+        );
+        let empty: Oid = commit(&repo, "main", "squash! Star(scope): summary", &[squash]);
+        let find = |o: Oid| repo.find_commit(o).expect("commit");
+        let squashed: CommitMessage =
+            CommitMessage::parse(squash_message(&find(target), &find(squash), &template).as_str())
+                .expect("valid message");
+        assert_eq!(
+            squashed.why,
+            vec!["keep the flags", "drop the cache", "sort the flags"]
+        );
+        assert_eq!(squashed.summary, m.summary);
+        assert_eq!(
+            squash_message(&find(target), &find(empty), &template),
+            message
+        );
+        let plain: Oid = commit(&repo, "other", "update things", &[]);
+        assert_eq!(
+            squash_message(&find(plain), &find(squash), &template),
+            "update things\n\ndrop the cache\nsort the flags\n"
+        );
+    }
+
+    #[test]
+    fn unpushed_needs_the_upstream_of_the_branch() {
+        let dir: TempDir = tempfile::tempdir().expect("temporary directory");
+        let repo: Repository = Repository::init(dir.path()).expect("repository");
+        let base: Oid = commit(&repo, "main", "base", &[]);
+        let pushed: Oid = commit(&repo, "main", "pushed", &[base]);
+        let local: Oid = commit(&repo, "main", "local", &[pushed]);
+        repo.set_head("refs/heads/main").expect("head");
+        assert!(unpushed(&repo).is_err());
+        repo.remote("origin", "https://example.com/repo.git")
+            .expect("remote");
+        repo.reference("refs/remotes/origin/main", pushed, true, "fetch")
+            .expect("remote branch");
+        repo.reference("refs/remotes/origin/other", local, true, "fetch")
+            .expect("other remote branch");
+        repo.find_branch("main", git2::BranchType::Local)
+            .expect("branch")
+            .set_upstream(Some("origin/main"))
+            .expect("upstream");
+        assert_eq!(unpushed(&repo).expect("unpushed"), vec![local]);
+    }
+}
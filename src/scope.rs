@@ -0,0 +1,205 @@
+use crate::message::CommitMessage;
+use cargo_metadata::{Metadata, MetadataCommand, Package};
+use git2::{Diff, Repository, Tree};
+use inquire::autocompletion::{Autocomplete, Replacement};
+use inquire::CustomUserError;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const CRATE_WEIGHT: usize = 3;
+const TARGET_WEIGHT: usize = 3;
+const MODULE_WEIGHT: usize = 2;
+const DIRECTORY_WEIGHT: usize = 1;
+const HISTORY_DEPTH: usize = 500;
+const ROOT_FILES: [&str; 2] = ["main", "lib"];
+
+///
+/// Complete the scope prompt with the suggestions first, then the scopes used in the history.
+///
+#[derive(Clone)]
+pub struct ScopeCompleter {
+    scopes: Vec<String>,
+}
+
+impl ScopeCompleter {
+    #[must_use]
+    pub fn new(suggestions: &[String], history: &[String]) -> Self {
+        let mut scopes: Vec<String> = suggestions.to_vec();
+        for h in history {
+            if scopes.contains(h).eq(&false) {
+                scopes.push(h.clone());
+            }
+        }
+        Self { scopes }
+    }
+}
+
+impl Autocomplete for ScopeCompleter {
+    fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
+        let input: String = input.to_lowercase();
+        Ok(self
+            .scopes
+            .iter()
+            .filter(|s| s.to_lowercase().contains(input.as_str()))
+            .cloned()
+            .collect())
+    }
+
+    fn get_completion(
+        &mut self,
+        input: &str,
+        highlighted_suggestion: Option<String>,
+    ) -> Result<Replacement, CustomUserError> {
+        Ok(highlighted_suggestion.or_else(|| {
+            let input: String = input.to_lowercase();
+            self.scopes
+                .iter()
+                .find(|s| s.to_lowercase().starts_with(input.as_str()))
+                .cloned()
+        }))
+    }
+}
+
+fn staged(repo: &Repository) -> Vec<PathBuf> {
+    let head: Option<Tree<'_>> = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let Ok(diff) = repo.diff_tree_to_index(head.as_ref(), None, None) else {
+        return Vec::new();
+    };
+    paths(&diff)
+}
+
+fn paths(diff: &Diff<'_>) -> Vec<PathBuf> {
+    diff.deltas()
+        .filter_map(|d| d.new_file().path().or_else(|| d.old_file().path()))
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+fn package_of<'a>(packages: &'a [&'a Package], file: &Path) -> Option<&'a Package> {
+    packages
+        .iter()
+        .filter(|p| {
+            file.starts_with(
+                p.manifest_path
+                    .parent()
+                    .map_or(Path::new(""), |d| d.as_std_path()),
+            )
+        })
+        .max_by_key(|p| p.manifest_path.components().count())
+        .copied()
+}
+
+fn module_of(file: &Path, root: &Path) -> Option<String> {
+    let relative: &Path = file.strip_prefix(root).ok()?;
+    let mut components = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy());
+    if components.next()?.ne("src") {
+        return None;
+    }
+    let module: String = components.next()?.to_string();
+    let module: &str = module.strip_suffix(".rs").unwrap_or(module.as_str());
+    if ROOT_FILES.contains(&module) || module.eq("mod") {
+        return None;
+    }
+    Some(module.to_string())
+}
+
+fn add(scores: &mut BTreeMap<String, usize>, scope: &str, weight: usize, max: usize) {
+    if scope.is_empty() || scope.chars().count().gt(&max) {
+        return;
+    }
+    *scores.entry(scope.to_string()).or_insert(0) += weight;
+}
+
+///
+/// The scopes suggested for the files staged in the repository `r`, the best first, the scopes longer than `max`
+/// characters being skipped.
+///
+/// A staged file suggests the name of its crate and of its cargo target, its module under `src` and its directories.
+///
+#[must_use]
+pub fn suggestions(r: &str, max: usize) -> Vec<String> {
+    let Ok(repo) = Repository::open(r) else {
+        return Vec::new();
+    };
+    let root: PathBuf = repo
+        .workdir()
+        .map_or_else(|| PathBuf::from(r), Path::to_path_buf)
+        .canonicalize()
+        .unwrap_or_default();
+    let metadata: Option<Metadata> = MetadataCommand::new()
+        .no_deps()
+        .current_dir(root.as_path())
+        .exec()
+        .ok();
+    let packages: Vec<&Package> = metadata
+        .as_ref()
+        .map(|m| m.workspace_packages())
+        .unwrap_or_default();
+    let mut scores: BTreeMap<String, usize> = BTreeMap::new();
+    for file in staged(&repo) {
+        let absolute: PathBuf = root.join(file.as_path());
+        if let Some(package) = package_of(&packages, absolute.as_path()) {
+            add(&mut scores, package.name.as_str(), CRATE_WEIGHT, max);
+            for target in package
+                .targets
+                .iter()
+                .filter(|t| t.src_path.as_std_path().eq(absolute.as_path()))
+                .filter(|t| t.name.ne(&package.name))
+            {
+                add(&mut scores, target.name.as_str(), TARGET_WEIGHT, max);
+            }
+            let dir: &Path = package
+                .manifest_path
+                .parent()
+                .map_or(root.as_path(), |d| d.as_std_path());
+            if let Some(module) = module_of(absolute.as_path(), dir) {
+                add(&mut scores, module.as_str(), MODULE_WEIGHT, max);
+            }
+        }
+        if let Some(parent) = file.parent() {
+            for dir in parent.components() {
+                add(
+                    &mut scores,
+                    dir.as_os_str().to_string_lossy().as_ref(),
+                    DIRECTORY_WEIGHT,
+                    max,
+                );
+            }
+        }
+    }
+    let mut ranked: Vec<(String, usize)> = scores.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked.into_iter().map(|(s, _)| s).collect()
+}
+
+///
+/// The scopes used by the last commits of the repository `r`, the most used first, the scopes longer than `max`
+/// characters being skipped.
+///
+#[must_use]
+pub fn history(r: &str, max: usize) -> Vec<String> {
+    let Ok(repo) = Repository::open(r) else {
+        return Vec::new();
+    };
+    let Ok(mut revwalk) = repo.revwalk() else {
+        return Vec::new();
+    };
+    if revwalk.push_head().is_err() {
+        return Vec::new();
+    }
+    let mut scores: BTreeMap<String, usize> = BTreeMap::new();
+    for oid in revwalk.take(HISTORY_DEPTH).flatten() {
+        if let Some(m) = repo
+            .find_commit(oid)
+            .ok()
+            .and_then(|c| CommitMessage::parse(c.message().unwrap_or_default()))
+        {
+            add(&mut scores, m.scope.as_str(), 1, max);
+        }
+    }
+    let mut ranked: Vec<(String, usize)> = scores.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked.into_iter().map(|(s, _)| s).collect()
+}
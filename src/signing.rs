@@ -0,0 +1,387 @@
+use crate::hook;
+use git2::{Buf, Commit, Config, Index, Oid, Repository, Signature, Tree};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use tempfile::TempDir;
+
+///
+/// The signature formats of `gpg.format`.
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    OpenPgp,
+    X509,
+    Ssh,
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::OpenPgp => "openpgp",
+            Self::X509 => "x509",
+            Self::Ssh => "ssh",
+        })
+    }
+}
+
+///
+/// Sign the commits as configured by `commit.gpgsign`, `gpg.format` and `user.signingkey`.
+///
+#[derive(Clone, Debug)]
+pub struct Signer {
+    pub format: Format,
+    pub key: Option<String>,
+    pub program: String,
+}
+
+fn string(config: &Config, name: &str) -> Option<String> {
+    config
+        .get_string(name)
+        .ok()
+        .filter(|x| x.is_empty().eq(&false))
+}
+
+fn expand(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+impl Signer {
+    ///
+    /// The signer configured for the repository `repo`, `None` when `commit.gpgsign` is not enabled.
+    ///
+    /// # Errors
+    ///
+    /// If the configuration cannot be read or `gpg.format` is unknown.
+    ///
+    pub fn new(repo: &Repository) -> Result<Option<Self>, String> {
+        let config: Config = repo.config().map_err(|e| e.message().to_string())?;
+        if config
+            .get_bool("commit.gpgsign")
+            .unwrap_or(false)
+            .eq(&false)
+        {
+            return Ok(None);
+        }
+        Self::from_config(&config).map(Some)
+    }
+
+    ///
+    /// The signer described by `config`, whatever the value of `commit.gpgsign`.
+    ///
+    /// # Errors
+    ///
+    /// If `gpg.format` is unknown.
+    ///
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let format: Format = match string(config, "gpg.format").as_deref() {
+            None | Some("openpgp") => Format::OpenPgp,
+            Some("x509") => Format::X509,
+            Some("ssh") => Format::Ssh,
+            Some(x) => return Err(format!("gpg.format : {x} is not openpgp, x509 or ssh")),
+        };
+        let program: String = match format {
+            Format::OpenPgp => string(config, "gpg.openpgp.program")
+                .or_else(|| string(config, "gpg.program"))
+                .unwrap_or_else(|| String::from("gpg")),
+            Format::X509 => {
+                string(config, "gpg.x509.program").unwrap_or_else(|| String::from("gpgsm"))
+            }
+            Format::Ssh => {
+                string(config, "gpg.ssh.program").unwrap_or_else(|| String::from("ssh-keygen"))
+            }
+        };
+        Ok(Self {
+            format,
+            key: string(config, "user.signingkey"),
+            program,
+        })
+    }
+
+    ///
+    /// Sign `content` for the committer `committer`, the key of the committer is used when no key is configured.
+    ///
+    /// # Errors
+    ///
+    /// If the signing program cannot be run or fails.
+    ///
+    pub fn sign(&self, content: &str, committer: &Signature<'_>) -> Result<String, String> {
+        match self.format {
+            Format::OpenPgp | Format::X509 => self.sign_gpg(content, committer),
+            Format::Ssh => self.sign_ssh(content),
+        }
+    }
+
+    fn sign_gpg(&self, content: &str, committer: &Signature<'_>) -> Result<String, String> {
+        let key: String = self.key.clone().unwrap_or_else(|| {
+            format!(
+                "{} <{}>",
+                committer.name().unwrap_or_default(),
+                committer.email().unwrap_or_default()
+            )
+        });
+        let mut child = Command::new(self.program.as_str())
+            .args(["--status-fd=2", "-bsau", key.as_str()])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to run {} : {e}", self.program))?;
+        child
+            .stdin
+            .take()
+            .expect("no stdin")
+            .write_all(content.as_bytes())
+            .map_err(|e| format!("failed to send the commit to {} : {e}", self.program))?;
+        let output: Output = child
+            .wait_with_output()
+            .map_err(|e| format!("failed to run {} : {e}", self.program))?;
+        let status: String = String::from_utf8_lossy(&output.stderr).to_string();
+        if output.status.success().eq(&false) || status.contains("[GNUPG:] SIG_CREATED").eq(&false)
+        {
+            return Err(format!(
+                "{} failed to sign with the key {key} :\n{}",
+                self.program,
+                status.trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn sign_ssh(&self, content: &str) -> Result<String, String> {
+        let Some(key) = self.key.as_deref() else {
+            return Err(String::from(
+                "user.signingkey must give the ssh key used with gpg.format=ssh",
+            ));
+        };
+        let dir: TempDir = tempfile::Builder::new()
+            .prefix("zazen-")
+            .tempdir()
+            .map_err(|e| format!("failed to create a temporary directory : {e}"))?;
+        let buffer: PathBuf = dir.path().join("commit");
+        let signature: PathBuf = dir.path().join("commit.sig");
+        let literal: Option<&str> = key
+            .strip_prefix("key::")
+            .or_else(|| key.starts_with("ssh-").then_some(key));
+        let key_file: PathBuf = literal.map_or_else(
+            || PathBuf::from(expand(key)),
+            |_| dir.path().join("key.pub"),
+        );
+        if let Some(k) = literal {
+            fs::write(&key_file, k).map_err(|e| format!("failed to write the ssh key : {e}"))?;
+        }
+        fs::write(&buffer, content)
+            .map_err(|e| format!("failed to write the commit to sign : {e}"))?;
+        let mut command: Command = Command::new(self.program.as_str());
+        command
+            .args(["-Y", "sign", "-n", "git", "-f"])
+            .arg(&key_file);
+        if literal.is_some() {
+            command.arg("-U");
+        }
+        let output: Result<Output, String> = command
+            .arg(&buffer)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("failed to run {} : {e}", self.program));
+        output.and_then(|o| {
+            if o.status.success() {
+                fs::read_to_string(&signature)
+                    .map_err(|e| format!("failed to read the ssh signature : {e}"))
+            } else {
+                Err(format!(
+                    "{} failed to sign with the key {key} :\n{}",
+                    self.program,
+                    String::from_utf8_lossy(&o.stderr).trim()
+                ))
+            }
+        })
+    }
+}
+
+impl Display for Signer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} signature with {} using the key {}",
+            self.format,
+            self.program,
+            self.key.as_deref().unwrap_or("of the committer")
+        )
+    }
+}
+
+///
+/// Point HEAD, or the branch checked out, to the commit `oid`, `action` is written in the reflog.
+///
+/// # Errors
+///
+/// If the reference cannot be updated.
+///
+pub fn update_head(repo: &Repository, oid: Oid, action: &str, message: &str) -> Result<(), String> {
+    let log: String = format!("{action}: {}", message.lines().next().unwrap_or_default());
+    repo.find_reference("HEAD")
+        .and_then(|head| match head.symbolic_target() {
+            Some(target) => repo.reference(target, oid, true, log.as_str()).map(|_| ()),
+            None => repo.set_head_detached(oid),
+        })
+        .map_err(|e| format!("failed to update HEAD : {}", e.message()))
+}
+
+///
+/// The signature of the user, used as committer.
+///
+/// # Errors
+///
+/// If `user.name` or `user.email` is not configured.
+///
+pub fn committer(repo: &Repository) -> Result<Signature<'static>, String> {
+    repo.signature().map_err(|e| {
+        format!(
+            "user.name and user.email must be configured : {}",
+            e.message()
+        )
+    })
+}
+
+///
+/// Write a commit without moving any reference, the commit is signed when `commit.gpgsign` is enabled.
+///
+/// # Errors
+///
+/// If the commit cannot be created or signed, the message explain why.
+///
+pub fn write(
+    repo: &Repository,
+    author: &Signature<'_>,
+    committer: &Signature<'_>,
+    message: &str,
+    tree: &Tree<'_>,
+    parents: &[&Commit<'_>],
+) -> Result<Oid, String> {
+    let message: String = git2::message_prettify(message, None)
+        .map_err(|e| format!("invalid commit message : {}", e.message()))?;
+    let Some(signer) = Signer::new(repo)? else {
+        return repo
+            .commit(None, author, committer, message.as_str(), tree, parents)
+            .map_err(|e| format!("failed to create the commit : {}", e.message()));
+    };
+    let content: Buf = repo
+        .commit_create_buffer(author, committer, message.as_str(), tree, parents)
+        .map_err(|e| format!("failed to create the commit : {}", e.message()))?;
+    let content: &str = content.as_str().expect("the commit is not valid utf-8");
+    let signature: String = signer
+        .sign(content, committer)
+        .map_err(|e| format!("failed to sign the commit, {signer} :\n{e}"))?;
+    repo.commit_signed(content, signature.as_str(), None)
+        .map_err(|e| format!("failed to create the signed commit : {}", e.message()))
+}
+
+///
+/// Run the pre-commit hook then the commit-msg hook when `verify` is set, the index being reloaded after the
+/// pre-commit hook, and return the message to commit.
+///
+fn verified(
+    repo: &Repository,
+    index: &mut Index,
+    message: &str,
+    verify: bool,
+) -> Result<String, String> {
+    if verify.eq(&false) {
+        return Ok(message.to_string());
+    }
+    index
+        .write()
+        .map_err(|e| format!("failed to write the index : {}", e.message()))?;
+    hook::pre_commit(repo)?;
+    index
+        .read(true)
+        .map_err(|e| format!("failed to read the index : {}", e.message()))?;
+    hook::commit_message(repo, message)
+}
+
+fn tree<'r>(repo: &'r Repository, index: &mut Index) -> Result<Tree<'r>, String> {
+    index
+        .write_tree_to(repo)
+        .and_then(|t| repo.find_tree(t))
+        .map_err(|e| format!("failed to write the index : {}", e.message()))
+}
+
+///
+/// Commit the content of `index` in `repo` with `message`, the commit is signed when `commit.gpgsign` is enabled.
+///
+/// The pre-commit and commit-msg hooks are run before when `verify` is set, the post-commit hook after.
+///
+/// # Errors
+///
+/// If a hook fails, or the commit cannot be created or signed, the message explain why.
+///
+pub fn commit(
+    repo: &Repository,
+    index: &mut Index,
+    message: &str,
+    verify: bool,
+) -> Result<Oid, String> {
+    let message: String = verified(repo, index, message, verify)?;
+    let message: &str = message.as_str();
+    let author: Signature<'_> = committer(repo)?;
+    let tree: Tree<'_> = tree(repo, index)?;
+    let parents: Vec<Commit<'_>> = repo
+        .head()
+        .ok()
+        .and_then(|h| h.peel_to_commit().ok())
+        .into_iter()
+        .collect();
+    let parents: Vec<&Commit<'_>> = parents.iter().collect();
+    let oid: Oid = write(repo, &author, &author, message, &tree, &parents)?;
+    let action: &str = if parents.is_empty() {
+        "commit (initial)"
+    } else {
+        "commit"
+    };
+    update_head(repo, oid, action, message)?;
+    hook::post_commit(repo);
+    Ok(oid)
+}
+
+///
+/// Replace the last commit of `repo` by the content of `index` with `message`, the author of the last commit is kept.
+///
+/// The pre-commit and commit-msg hooks are run before when `verify` is set, the post-commit hook after.
+///
+/// # Errors
+///
+/// If there is no commit to amend, a hook fails, or the commit cannot be created or signed.
+///
+pub fn amend(
+    repo: &Repository,
+    index: &mut Index,
+    message: &str,
+    verify: bool,
+) -> Result<Oid, String> {
+    let head: Commit<'_> = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|_| String::from("there is no commit to amend"))?;
+    let message: String = verified(repo, index, message, verify)?;
+    let message: &str = message.as_str();
+    let tree: Tree<'_> = tree(repo, index)?;
+    let parents: Vec<Commit<'_>> = head.parents().collect();
+    let parents: Vec<&Commit<'_>> = parents.iter().collect();
+    let oid: Oid = write(
+        repo,
+        &head.author(),
+        &committer(repo)?,
+        message,
+        &tree,
+        &parents,
+    )?;
+    update_head(repo, oid, "commit (amend)", message)?;
+    hook::post_commit(repo);
+    Ok(oid)
+}
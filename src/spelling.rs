@@ -0,0 +1,903 @@
+use crate::harvest;
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag, TagEnd};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::{self, read, read_to_string};
+use std::io::Write;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR_STR};
+
+/// The words of the project, one per line, in the zazen directory of the repository.
+pub const DICTIONARY_FILE: &str = "dictionary.txt";
+/// The directory of the `.dic` and `.aff` files of the project or of the user configuration.
+pub const DICTIONARIES_DIR: &str = "dictionaries";
+
+const SYSTEM_DIRS: [&str; 6] = [
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/usr/local/share/hunspell",
+    "/Library/Spelling",
+    "/opt/homebrew/share/hunspell",
+];
+const SUGGESTIONS: usize = 5;
+
+type Flag = u64;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FlagMode {
+    Short,
+    Long,
+    Num,
+    Utf8,
+}
+
+#[derive(Clone)]
+enum Condition {
+    Any,
+    Set(Vec<char>, bool),
+}
+
+impl Condition {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Set(chars, negated) => chars.contains(&c).ne(negated),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Affix {
+    flag: Flag,
+    cross: bool,
+    strip: String,
+    add: String,
+    condition: Vec<Condition>,
+    continuation: Vec<Flag>,
+}
+
+impl Affix {
+    ///
+    /// The root of `word` when it ends with this suffix.
+    ///
+    fn strip_suffix(&self, word: &str) -> Option<String> {
+        let rest: &str = word.strip_suffix(self.add.as_str())?;
+        if rest.is_empty() {
+            return None;
+        }
+        let root: String = format!("{rest}{}", self.strip);
+        let chars: Vec<char> = root.chars().collect();
+        let n: usize = self.condition.len();
+        (chars.len().ge(&n)
+            && self
+                .condition
+                .iter()
+                .zip(&chars[chars.len() - n..])
+                .all(|(c, x)| c.matches(*x)))
+        .then_some(root)
+    }
+
+    ///
+    /// The root of `word` when it starts with this prefix.
+    ///
+    fn strip_prefix(&self, word: &str) -> Option<String> {
+        let rest: &str = word.strip_prefix(self.add.as_str())?;
+        if rest.is_empty() {
+            return None;
+        }
+        let root: String = format!("{}{rest}", self.strip);
+        let chars: Vec<char> = root.chars().collect();
+        (chars.len().ge(&self.condition.len())
+            && self
+                .condition
+                .iter()
+                .zip(&chars)
+                .all(|(c, x)| c.matches(*x)))
+        .then_some(root)
+    }
+}
+
+///
+/// A Hunspell dictionary, the stems of a `.dic` file with the affix rules of its `.aff` file.
+///
+#[derive(Default)]
+pub struct Dictionary {
+    words: HashMap<String, Vec<Flag>>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    forbidden: Option<Flag>,
+    need_affix: Option<Flag>,
+    keep_case: Option<Flag>,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+}
+
+fn decode(bytes: &[u8]) -> String {
+    let text: String = String::from_utf8_lossy(bytes).to_string();
+    let latin: bool = text.lines().any(|l| {
+        l.trim().strip_prefix("SET ").is_some_and(|s| {
+            let s: String = s.trim().to_uppercase();
+            s.starts_with("ISO8859") || s.starts_with("ISO-8859")
+        })
+    });
+    if latin {
+        bytes.iter().map(|b| char::from(*b)).collect()
+    } else {
+        text
+    }
+}
+
+fn parse_flags(text: &str, mode: FlagMode, aliases: &[Vec<Flag>]) -> Vec<Flag> {
+    if aliases.is_empty().eq(&false) {
+        if let Ok(i) = text.parse::<usize>() {
+            return aliases.get(i.wrapping_sub(1)).cloned().unwrap_or_default();
+        }
+    }
+    match mode {
+        FlagMode::Short | FlagMode::Utf8 => {
+            text.chars().map(|c| Flag::from(u32::from(c))).collect()
+        }
+        FlagMode::Long => text
+            .chars()
+            .collect::<Vec<char>>()
+            .chunks(2)
+            .map(|c| {
+                c.iter()
+                    .fold(0, |f, x| (f << 21) | Flag::from(u32::from(*x)))
+            })
+            .collect(),
+        FlagMode::Num => text
+            .split(',')
+            .filter_map(|n| n.trim().parse::<Flag>().ok())
+            .collect(),
+    }
+}
+
+fn parse_condition(text: &str) -> Vec<Condition> {
+    let mut condition: Vec<Condition> = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => condition.push(Condition::Any),
+            '[' => {
+                let mut set: Vec<char> = Vec::new();
+                let mut negated: bool = false;
+                for x in chars.by_ref() {
+                    match x {
+                        ']' => break,
+                        '^' if set.is_empty() && negated.eq(&false) => negated = true,
+                        _ => set.push(x),
+                    }
+                }
+                condition.push(Condition::Set(set, negated));
+            }
+            _ => condition.push(Condition::Set(vec![c], false)),
+        }
+    }
+    condition
+}
+
+///
+/// Split a `.dic` line in its word and its flags, a `\/` being a slash of the word.
+///
+fn split_entry(line: &str) -> (String, &str) {
+    let entry: &str = line.split(['\t', ' ']).next().unwrap_or_default();
+    let mut word: String = String::new();
+    let mut chars = entry.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if chars.peek().is_some_and(|(_, n)| n.eq(&'/')) => {
+                word.push('/');
+                chars.next();
+            }
+            '/' => return (word, &entry[i + 1..]),
+            _ => word.push(c),
+        }
+    }
+    (word, "")
+}
+
+impl Dictionary {
+    ///
+    /// Parse the content of a `.aff` and of a `.dic` file.
+    ///
+    #[must_use]
+    pub fn parse(aff: &str, dic: &str) -> Self {
+        let mut d: Self = Self::default();
+        let mut mode: FlagMode = FlagMode::Short;
+        let mut aliases: Vec<Vec<Flag>> = Vec::new();
+        let mut headers: HashMap<(bool, String), bool> = HashMap::new();
+        let flag = |t: &str, mode: FlagMode, aliases: &[Vec<Flag>]| {
+            parse_flags(t, mode, aliases).first().copied()
+        };
+        for line in aff.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["FLAG", m, ..] => {
+                    mode = match *m {
+                        "long" => FlagMode::Long,
+                        "num" => FlagMode::Num,
+                        "UTF-8" => FlagMode::Utf8,
+                        _ => FlagMode::Short,
+                    };
+                }
+                ["AF", flags, ..] if flags.parse::<usize>().is_err() => {
+                    aliases.push(parse_flags(flags, mode, &[]));
+                }
+                ["FORBIDDENWORD", f, ..] => d.forbidden = flag(f, mode, &[]),
+                ["NEEDAFFIX", f, ..] => d.need_affix = flag(f, mode, &[]),
+                ["KEEPCASE", f, ..] => d.keep_case = flag(f, mode, &[]),
+                ["TRY", chars, ..] => d.try_chars = chars.chars().collect(),
+                ["REP", from, to, ..] if from.parse::<usize>().is_err() => d
+                    .replacements
+                    .push((from.replace('_', " "), to.replace('_', " "))),
+                [kind @ ("PFX" | "SFX"), f, cross, count]
+                    if count.parse::<usize>().is_ok() && (cross.eq(&"Y") || cross.eq(&"N")) =>
+                {
+                    headers.insert((kind.eq(&"PFX"), (*f).to_string()), cross.eq(&"Y"));
+                }
+                [kind @ ("PFX" | "SFX"), f, strip, add, rest @ ..] => {
+                    let prefix: bool = kind.eq(&"PFX");
+                    let Some(cross) = headers.get(&(prefix, (*f).to_string())) else {
+                        continue;
+                    };
+                    let Some(id) = flag(f, mode, &[]) else {
+                        continue;
+                    };
+                    let (add, continuation) = add.split_once('/').unwrap_or((add, ""));
+                    let zero = |s: &str| {
+                        if s.eq("0") {
+                            String::new()
+                        } else {
+                            s.to_string()
+                        }
+                    };
+                    let affix: Affix = Affix {
+                        flag: id,
+                        cross: *cross,
+                        strip: zero(strip),
+                        add: zero(add),
+                        condition: parse_condition(rest.first().copied().unwrap_or(".")),
+                        continuation: parse_flags(continuation, mode, &aliases),
+                    };
+                    if prefix {
+                        d.prefixes.push(affix);
+                    } else {
+                        d.suffixes.push(affix);
+                    }
+                }
+                _ => {}
+            }
+        }
+        for line in dic
+            .lines()
+            .skip_while(|l| l.trim().parse::<usize>().is_err())
+            .skip(1)
+        {
+            if line.trim().is_empty() || line.starts_with('\t') {
+                continue;
+            }
+            let (word, flags) = split_entry(line);
+            if word.is_empty() {
+                continue;
+            }
+            d.words
+                .entry(word)
+                .or_default()
+                .extend(parse_flags(flags, mode, &aliases));
+        }
+        d
+    }
+
+    ///
+    /// Load `<lang>.aff` and `<lang>.dic` from `dir`.
+    ///
+    /// # Errors
+    ///
+    /// If one of the files cannot be read.
+    ///
+    pub fn load(dir: &Path, lang: &str) -> Result<Self, String> {
+        let file = |ext: &str| {
+            let path: PathBuf = dir.join(format!("{lang}.{ext}"));
+            read(&path)
+                .map(|b| decode(&b))
+                .map_err(|e| format!("failed to read {} : {e}", path.display()))
+        };
+        Ok(Self::parse(file("aff")?.as_str(), file("dic")?.as_str()))
+    }
+
+    fn has(&self, word: &str, flag: Flag, other: Option<Flag>) -> bool {
+        self.words.get(word).is_some_and(|f| {
+            f.contains(&flag)
+                && other.is_none_or(|o| f.contains(&o))
+                && self.forbidden.is_none_or(|x| f.contains(&x).eq(&false))
+        })
+    }
+
+    fn lookup(&self, word: &str) -> bool {
+        if let Some(flags) = self.words.get(word) {
+            let flagged = |x: Option<Flag>| x.is_some_and(|x| flags.contains(&x));
+            if flagged(self.forbidden) {
+                return false;
+            }
+            if flagged(self.need_affix).eq(&false) {
+                return true;
+            }
+        }
+        for sfx in &self.suffixes {
+            let Some(root) = sfx.strip_suffix(word) else {
+                continue;
+            };
+            if self.has(root.as_str(), sfx.flag, None) {
+                return true;
+            }
+            for inner in self
+                .suffixes
+                .iter()
+                .filter(|s| s.continuation.contains(&sfx.flag))
+            {
+                if inner
+                    .strip_suffix(root.as_str())
+                    .is_some_and(|r| self.has(r.as_str(), inner.flag, None))
+                {
+                    return true;
+                }
+            }
+        }
+        for pfx in &self.prefixes {
+            let Some(root) = pfx.strip_prefix(word) else {
+                continue;
+            };
+            if self.has(root.as_str(), pfx.flag, None) {
+                return true;
+            }
+            if pfx.cross
+                && self.suffixes.iter().filter(|s| s.cross).any(|s| {
+                    s.strip_suffix(root.as_str())
+                        .is_some_and(|r| self.has(r.as_str(), pfx.flag, Some(s.flag)))
+                })
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn keeps_case(&self, word: &str) -> bool {
+        self.keep_case
+            .is_some_and(|k| self.words.get(word).is_some_and(|f| f.contains(&k)))
+    }
+
+    ///
+    /// Check if `word` is spelled correctly, a capitalized or an upper case word being checked in lower case too.
+    ///
+    #[must_use]
+    pub fn check(&self, word: &str) -> bool {
+        if self.lookup(word) {
+            return true;
+        }
+        let lower: String = word.to_lowercase();
+        if lower.eq(word) {
+            return false;
+        }
+        let mut chars = word.chars();
+        let first: String = chars.next().map(|c| c.to_string()).unwrap_or_default();
+        let rest: String = chars.collect();
+        let capitalized: bool = rest.to_lowercase().eq(&rest);
+        let upper: bool = word.to_uppercase().eq(word);
+        let title: String = format!("{first}{}", rest.to_lowercase());
+        (upper && title.ne(word) && self.lookup(title.as_str()))
+            || ((capitalized || upper)
+                && self.keeps_case(lower.as_str()).eq(&false)
+                && self.lookup(lower.as_str()))
+    }
+
+    ///
+    /// The corrections of `word`, the replacements of the dictionary first.
+    ///
+    #[must_use]
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let chars: Vec<char> = word.chars().collect();
+        let alphabet: Vec<char> = if self.try_chars.is_empty() {
+            ('a'..='z').collect()
+        } else {
+            self.try_chars.clone()
+        };
+        let mut candidates: Vec<String> = Vec::new();
+        for (from, to) in &self.replacements {
+            for (i, _) in word.match_indices(from.as_str()) {
+                candidates.push(format!("{}{to}{}", &word[..i], &word[i + from.len()..]));
+            }
+        }
+        let text = |c: &[char]| c.iter().collect::<String>();
+        for i in 0..chars.len() {
+            let mut c: Vec<char> = chars.clone();
+            if i + 1 < chars.len() {
+                c.swap(i, i + 1);
+                candidates.push(text(&c));
+            }
+            let mut c: Vec<char> = chars.clone();
+            c.remove(i);
+            candidates.push(text(&c));
+            for a in &alphabet {
+                let mut c: Vec<char> = chars.clone();
+                c[i] = *a;
+                candidates.push(text(&c));
+            }
+        }
+        for i in 0..=chars.len() {
+            for a in &alphabet {
+                let mut c: Vec<char> = chars.clone();
+                c.insert(i, *a);
+                candidates.push(text(&c));
+            }
+        }
+        for i in 1..chars.len() {
+            candidates.push(format!("{} {}", text(&chars[..i]), text(&chars[i..])));
+        }
+        let mut seen: HashSet<String> = HashSet::new();
+        candidates
+            .into_iter()
+            .filter(|c| c.ne(word) && seen.insert(c.clone()))
+            .filter(|c| c.split(' ').all(|w| self.check(w)))
+            .take(SUGGESTIONS)
+            .collect()
+    }
+}
+
+///
+/// A word of a text unknown by the dictionary and the project, with its byte offset and its corrections.
+///
+#[derive(Clone, Debug)]
+pub struct Misspelling {
+    pub word: String,
+    pub offset: usize,
+    pub suggestions: Vec<String>,
+}
+
+///
+/// The words of `text` with their byte offset, the words containing a digit are skipped.
+///
+#[must_use]
+pub fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words: Vec<(usize, &str)> = Vec::new();
+    let mut start: Option<usize> = None;
+    let boundary = |c: char| c.is_alphanumeric().eq(&false) && c.ne(&'\'') && c.ne(&'’');
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, boundary(c)) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                let word: &str = text[s..i].trim_matches(['\'', '’']);
+                if word.is_empty().eq(&false) && word.chars().any(|x| x.is_numeric()).eq(&false) {
+                    let lead: usize =
+                        text[s..i].len() - text[s..i].trim_start_matches(['\'', '’']).len();
+                    words.push((s + lead, word));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+///
+/// Check if `chunk`, a text without spaces, is an url, an email or a code identifier.
+///
+fn is_code(chunk: &str) -> bool {
+    let chunk: &str = chunk.trim_matches(|c: char| c.is_alphanumeric().eq(&false));
+    let camel: bool = chunk
+        .chars()
+        .zip(chunk.chars().skip(1))
+        .any(|(a, b)| a.is_lowercase() && b.is_uppercase());
+    camel
+        || chunk.contains("://")
+        || chunk.starts_with("www.")
+        || chunk.contains(['_', '@', '/', '\\', '=', '<', '>', '{', '}'])
+        || chunk.contains("::")
+        || chunk.contains("()")
+        || (chunk.contains('.') && chunk.split('.').all(|p| p.is_empty().eq(&false)))
+}
+
+///
+/// The words of the prose of the markdown `text` with their byte offset, the code, the html, the urls and the code
+/// identifiers being skipped.
+///
+#[must_use]
+pub fn prose(text: &str) -> Vec<(usize, &str)> {
+    let mut found: Vec<(usize, &str)> = Vec::new();
+    let mut skipped: usize = 0;
+    for (event, range) in Parser::new_ext(text, Options::all()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::HtmlBlock | Tag::MetadataBlock(_))
+            | Event::Start(Tag::Link {
+                link_type: LinkType::Autolink | LinkType::Email,
+                ..
+            }) => skipped += 1,
+            Event::End(TagEnd::CodeBlock | TagEnd::HtmlBlock | TagEnd::MetadataBlock(_)) => {
+                skipped = skipped.saturating_sub(1);
+            }
+            Event::End(TagEnd::Link) if skipped.gt(&0) => skipped -= 1,
+            Event::Text(_) if skipped.eq(&0) => {
+                let source: &str = &text[range.clone()];
+                let mut start: Option<usize> = None;
+                for (i, c) in source.char_indices().chain([(source.len(), ' ')]) {
+                    match (start, c.is_whitespace()) {
+                        (None, false) => start = Some(i),
+                        (Some(s), true) => {
+                            let chunk: &str = &source[s..i];
+                            if is_code(chunk).eq(&false) {
+                                found.extend(
+                                    words(chunk)
+                                        .into_iter()
+                                        .map(|(o, w)| (range.start + s + o, w)),
+                                );
+                            }
+                            start = None;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+///
+/// The line and the column, from 1, of the byte `offset` of `text`.
+///
+#[must_use]
+pub fn position(text: &str, offset: usize) -> (usize, usize) {
+    let before: &str = &text[..offset];
+    let line: usize = before.matches('\n').count() + 1;
+    let column: usize = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+fn dictionary_dirs(r: &str) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = vec![PathBuf::from(format!(
+        "{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}{DICTIONARIES_DIR}"
+    ))];
+    if let Some(c) = crate::template::config_dir() {
+        dirs.push(PathBuf::from(format!(
+            "{c}{MAIN_SEPARATOR_STR}{DICTIONARIES_DIR}"
+        )));
+    }
+    if let Ok(paths) = std::env::var("DICPATH") {
+        dirs.extend(std::env::split_paths(&paths));
+    }
+    if let Ok(home) = std::env::var("HOME") {
+        dirs.push(PathBuf::from(format!("{home}/Library/Spelling")));
+    }
+    dirs.extend(SYSTEM_DIRS.iter().map(PathBuf::from));
+    dirs
+}
+
+fn project_file(r: &str) -> String {
+    format!("{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}{DICTIONARY_FILE}")
+}
+
+///
+/// Check the spelling of texts with Hunspell dictionaries and the words of the project.
+///
+#[derive(Default)]
+pub struct Checker {
+    dictionaries: Vec<Dictionary>,
+    missing: Vec<String>,
+    project: HashSet<String>,
+}
+
+impl Checker {
+    ///
+    /// The checker of the repository `r` for the languages `languages`, a word being known when one of their
+    /// dictionaries knows it, with the words of `zazen/dictionary.txt` and the identifiers harvested from the sources.
+    ///
+    /// The dictionaries are searched in `zazen/dictionaries`, the zazen configuration directory, `DICPATH` and the
+    /// system directories of Hunspell, the languages without dictionary being given by [`Checker::missing`].
+    ///
+    #[must_use]
+    pub fn new(r: &str, languages: &[String]) -> Self {
+        let dirs: Vec<PathBuf> = dictionary_dirs(r);
+        let mut dictionaries: Vec<Dictionary> = Vec::new();
+        let mut missing: Vec<String> = Vec::new();
+        for lang in languages {
+            match dirs
+                .iter()
+                .filter(|d| d.join(format!("{lang}.dic")).is_file())
+                .find_map(|d| Dictionary::load(d, lang).ok())
+            {
+                Some(d) => dictionaries.push(d),
+                None => missing.push(lang.to_string()),
+            }
+        }
+        let mut project: HashSet<String> = HashSet::new();
+        let listed: String = read_to_string(project_file(r)).unwrap_or_default();
+        let names: Vec<String> = harvest::words(r);
+        for w in listed
+            .lines()
+            .map(str::trim)
+            .filter(|l| l.is_empty().eq(&false) && l.starts_with('#').eq(&false))
+            .map(String::from)
+            .chain(names)
+        {
+            for (_, part) in words(w.as_str()) {
+                project.insert(part.to_lowercase());
+            }
+            project.insert(w.to_lowercase());
+        }
+        Self {
+            dictionaries,
+            missing,
+            project,
+        }
+    }
+
+    ///
+    /// The warning of the languages without dictionary, whose spelling is not checked, `None` when every language
+    /// has one.
+    ///
+    #[must_use]
+    pub fn missing(&self) -> Option<String> {
+        if self.missing.is_empty() {
+            return None;
+        }
+        let languages: String = self.missing.join(", ");
+        Some(format!(
+            "no {languages} dictionary found, the {languages} spelling is not checked"
+        ))
+    }
+
+    ///
+    /// Check if `word` is known by the project or one of the dictionaries, every word is known without dictionary,
+    /// [`Checker::missing`] telling the languages not checked.
+    ///
+    #[must_use]
+    pub fn check(&self, word: &str) -> bool {
+        self.project.contains(&word.to_lowercase())
+            || self.dictionaries.is_empty()
+            || self.dictionaries.iter().any(|d| d.check(word))
+    }
+
+    ///
+    /// Know `words` from now on, like the words of the project.
+    ///
+    pub fn add(&mut self, words: &[String]) {
+        self.project.extend(words.iter().map(|w| w.to_lowercase()));
+    }
+
+    ///
+    /// The misspelled words of `text`, in order.
+    ///
+    #[must_use]
+    pub fn misspelled(&self, text: &str) -> Vec<Misspelling> {
+        self.unknown(words(text))
+    }
+
+    ///
+    /// The misspelled words of the prose of the markdown `text`, in order.
+    ///
+    #[must_use]
+    pub fn misspelled_markdown(&self, text: &str) -> Vec<Misspelling> {
+        self.unknown(prose(text))
+    }
+
+    fn unknown(&self, words: Vec<(usize, &str)>) -> Vec<Misspelling> {
+        let mut suggestions: HashMap<String, Vec<String>> = HashMap::new();
+        words
+            .into_iter()
+            .filter(|(_, w)| self.check(w).eq(&false))
+            .map(|(offset, w)| Misspelling {
+                word: w.to_string(),
+                offset,
+                suggestions: suggestions
+                    .entry(w.to_string())
+                    .or_insert_with(|| {
+                        let mut all: Vec<String> = Vec::new();
+                        for x in self.dictionaries.iter().flat_map(|d| d.suggest(w)) {
+                            if all.contains(&x).eq(&false) {
+                                all.push(x);
+                            }
+                        }
+                        all.truncate(SUGGESTIONS);
+                        all
+                    })
+                    .clone(),
+            })
+            .collect()
+    }
+}
+
+///
+/// Add `words` to the `zazen/dictionary.txt` of the repository `r`, the words already there are skipped.
+///
+/// # Errors
+///
+/// If the dictionary cannot be written.
+///
+pub fn learn(r: &str, words: &[String]) -> Result<(), String> {
+    let path: String = project_file(r);
+    if let Some(dir) = Path::new(path.as_str()).parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("failed to create {} : {e}", dir.display()))?;
+    }
+    let known: BTreeSet<String> = read_to_string(path.as_str())
+        .unwrap_or_default()
+        .lines()
+        .map(|l| l.trim().to_lowercase())
+        .collect();
+    let mut f = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path.as_str())
+        .map_err(|e| format!("failed to open {path} : {e}"))?;
+    let mut added: BTreeSet<String> = BTreeSet::new();
+    for w in words {
+        if known.contains(&w.to_lowercase()).eq(&false) && added.insert(w.to_lowercase()) {
+            writeln!(f, "{w}").map_err(|e| format!("failed to write {path} : {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{position, prose, words, Checker, Dictionary};
+    use std::fs;
+
+    const AFF: &str = "SET UTF-8
+TRY esianrtolcdugmphbyfvkwz
+KEEPCASE K
+FORBIDDENWORD F
+NEEDAFFIX N
+REP 1
+REP f ph
+PFX A Y 1
+PFX A 0 re .
+SFX B Y 2
+SFX B 0 ed [^y]
+SFX B y ied y
+SFX C N 1
+SFX C 0 s .
+SFX D Y 1
+SFX D 0 er/B .
+";
+
+    const DIC: &str = "9
+work/ABC
+try/AB
+play/AC
+photo
+hello/K
+graf/F
+pseudo/NB
+read/D
+path\\/name
+";
+
+    fn dictionary() -> Dictionary {
+        Dictionary::parse(AFF, DIC)
+    }
+
+    #[test]
+    fn stems_are_known() {
+        let d: Dictionary = dictionary();
+        assert!(d.check("work"));
+        assert!(d.check("photo"));
+        assert!(d.check("path/name"));
+        assert!(d.check("wrok").eq(&false));
+    }
+
+    #[test]
+    fn prefixes_and_suffixes_are_expanded() {
+        let d: Dictionary = dictionary();
+        assert!(d.check("rework"));
+        assert!(d.check("worked"));
+        assert!(d.check("works"));
+        assert!(d.check("tried"));
+        assert!(d.check("tryed").eq(&false));
+        assert!(d.check("rephoto").eq(&false));
+        assert!(d.check("reader"));
+        assert!(d.check("readered"));
+    }
+
+    #[test]
+    fn cross_products_need_both_affixes_to_allow_them() {
+        let d: Dictionary = dictionary();
+        assert!(d.check("reworked"));
+        assert!(d.check("retried"));
+        assert!(d.check("replay"));
+        assert!(d.check("plays"));
+        assert!(d.check("replays").eq(&false));
+    }
+
+    #[test]
+    fn flags_forbid_or_restrict_words() {
+        let d: Dictionary = dictionary();
+        assert!(d.check("graf").eq(&false));
+        assert!(d.check("pseudo").eq(&false));
+        assert!(d.check("pseudoed"));
+    }
+
+    #[test]
+    fn case_is_folded_unless_kept() {
+        let d: Dictionary = dictionary();
+        assert!(d.check("Work"));
+        assert!(d.check("WORK"));
+        assert!(d.check("Reworked"));
+        assert!(d.check("wORK").eq(&false));
+        assert!(d.check("hello"));
+        assert!(d.check("Hello").eq(&false));
+    }
+
+    #[test]
+    fn suggest_gives_the_replacements_first() {
+        let d: Dictionary = dictionary();
+        assert_eq!(d.suggest("foto").first().map(String::as_str), Some("photo"));
+        assert!(d.suggest("wrok").contains(&String::from("work")));
+        assert!(d.suggest("workk").contains(&String::from("work")));
+        assert!(d.suggest("wrk").contains(&String::from("work")));
+        assert!(d.suggest("zzzzzz").is_empty());
+    }
+
+    #[test]
+    fn words_skip_numbers_and_keep_apostrophes() {
+        assert_eq!(
+            words("it's v2 'quoted' done."),
+            vec![(0, "it's"), (9, "quoted"), (17, "done")]
+        );
+    }
+
+    #[test]
+    fn prose_skips_the_code_the_html_and_the_urls() {
+        let text: &str = "Hello `code` world\n\n```\nfn main\n```\n\n<div>html</div>\n\nsee https://x.dev and snake_case or camelCase\n";
+        let found: Vec<(usize, &str)> = prose(text);
+        assert_eq!(
+            found.iter().map(|(_, w)| *w).collect::<Vec<&str>>(),
+            vec!["Hello", "world", "see", "and", "or"]
+        );
+        for (offset, word) in found {
+            assert_eq!(&text[offset..offset + word.len()], word);
+        }
+    }
+
+    #[test]
+    fn position_counts_lines_and_characters() {
+        assert_eq!(position("abc", 0), (1, 1));
+        assert_eq!(position("ab\ncd", 4), (2, 2));
+        assert_eq!(position("é\nàx", 5), (2, 2));
+    }
+
+    #[test]
+    fn checker_uses_the_project_words_and_reports_missing_dictionaries() {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let zazen = dir.path().join("zazen");
+        fs::create_dir_all(zazen.join("dictionaries")).expect("dictionaries directory");
+        fs::write(zazen.join("dictionaries").join("xx_XX.aff"), AFF).expect("aff");
+        fs::write(zazen.join("dictionaries").join("xx_XX.dic"), DIC).expect("dic");
+        fs::write(zazen.join("dictionary.txt"), "# words\nzazen\n").expect("project words");
+        let r: &str = dir.path().to_str().expect("utf-8 path");
+        let checker: Checker = Checker::new(r, &[String::from("xx_XX")]);
+        assert!(checker.missing().is_none());
+        assert!(checker.check("Zazen"));
+        assert_eq!(
+            checker
+                .misspelled("reworked the wrok")
+                .into_iter()
+                .map(|m| (m.word, m.offset))
+                .collect::<Vec<(String, usize)>>(),
+            vec![(String::from("the"), 9), (String::from("wrok"), 13)]
+        );
+        let missing: Checker = Checker::new(r, &[String::from("zz_ZZ")]);
+        assert_eq!(
+            missing.missing().as_deref(),
+            Some("no zz_ZZ dictionary found, the zz_ZZ spelling is not checked")
+        );
+        assert!(missing.check("anything"));
+    }
+}
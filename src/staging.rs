@@ -0,0 +1,537 @@
+use colored::Colorize;
+use git2::{
+    Diff, DiffDelta, DiffLine, DiffOptions, Index, IndexEntry, Object, Patch, Repository, Status,
+    StatusEntry, StatusOptions, Statuses, Tree,
+};
+use inquire::{Confirm, MultiSelect, Select};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+const STAGE: &str = "Stage this hunk";
+const SKIP: &str = "Skip this hunk";
+const SPLIT: &str = "Split this hunk into lines";
+const STAGE_REST: &str = "Stage this hunk and the rest of the file";
+const SKIP_REST: &str = "Skip this hunk and the rest of the file";
+const NO_NEWLINE: &str = "\\ No newline at end of file";
+
+///
+/// The kind of change of a path of the working tree or of the index.
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kind {
+    New,
+    Modified,
+    Deleted,
+    Renamed,
+    Typechange,
+    Conflicted,
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::New => "new",
+            Self::Modified => "modified",
+            Self::Deleted => "deleted",
+            Self::Renamed => "renamed",
+            Self::Typechange => "typechange",
+            Self::Conflicted => "conflicted",
+        })
+    }
+}
+
+///
+/// A changed path, `old_path` is the path before a rename.
+///
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub kind: Kind,
+    pub path: String,
+    pub old_path: Option<String>,
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.old_path {
+            Some(old) => write!(f, "{:<10} {old} -> {}", self.kind, self.path),
+            None => write!(f, "{:<10} {}", self.kind, self.path),
+        }
+    }
+}
+
+impl Entry {
+    fn new(kind: Kind, entry: &StatusEntry<'_>, delta: Option<DiffDelta<'_>>) -> Self {
+        let path: String = entry.path().unwrap_or_default().to_string();
+        let Some(delta) = delta.filter(|_| kind.eq(&Kind::Renamed)) else {
+            return Self {
+                kind,
+                path,
+                old_path: None,
+            };
+        };
+        let name = |f: Option<&Path>| f.map(|p| p.to_string_lossy().to_string());
+        Self {
+            kind,
+            path: name(delta.new_file().path()).unwrap_or(path),
+            old_path: name(delta.old_file().path()),
+        }
+    }
+
+    fn paths(&self) -> Vec<String> {
+        self.old_path.iter().chain([&self.path]).cloned().collect()
+    }
+}
+
+fn staged_kind(status: Status) -> Option<Kind> {
+    if status.is_index_new() {
+        Some(Kind::New)
+    } else if status.is_index_modified() {
+        Some(Kind::Modified)
+    } else if status.is_index_deleted() {
+        Some(Kind::Deleted)
+    } else if status.is_index_renamed() {
+        Some(Kind::Renamed)
+    } else if status.is_index_typechange() {
+        Some(Kind::Typechange)
+    } else {
+        None
+    }
+}
+
+fn unstaged_kind(status: Status) -> Option<Kind> {
+    if status.is_conflicted() {
+        Some(Kind::Conflicted)
+    } else if status.is_wt_new() {
+        Some(Kind::New)
+    } else if status.is_wt_modified() {
+        Some(Kind::Modified)
+    } else if status.is_wt_deleted() {
+        Some(Kind::Deleted)
+    } else if status.is_wt_renamed() {
+        Some(Kind::Renamed)
+    } else if status.is_wt_typechange() {
+        Some(Kind::Typechange)
+    } else {
+        None
+    }
+}
+
+///
+/// The changes of `repo`, staged in the index then not staged.
+///
+/// # Errors
+///
+/// If the status of the repository cannot be read.
+///
+pub fn entries(repo: &Repository) -> Result<(Vec<Entry>, Vec<Entry>), git2::Error> {
+    let mut opts: StatusOptions = StatusOptions::new();
+    opts.include_ignored(false)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true);
+    let statuses: Statuses<'_> = repo.statuses(Some(&mut opts))?;
+    let mut staged: Vec<Entry> = Vec::new();
+    let mut unstaged: Vec<Entry> = Vec::new();
+    for entry in &statuses {
+        if let Some(kind) = staged_kind(entry.status()) {
+            staged.push(Entry::new(kind, &entry, entry.head_to_index()));
+        }
+        if let Some(kind) = unstaged_kind(entry.status()) {
+            unstaged.push(Entry::new(kind, &entry, entry.index_to_workdir()));
+        }
+    }
+    Ok((staged, unstaged))
+}
+
+///
+/// Display the staged and the not staged changes in two sections.
+///
+pub fn print_entries(staged: &[Entry], unstaged: &[Entry]) {
+    if staged.is_empty().eq(&false) {
+        println!("\n  {}\n", "Changes to be committed :".green());
+        for e in staged {
+            println!("    {}", e.to_string().green());
+        }
+    }
+    if unstaged.is_empty().eq(&false) {
+        println!("\n  {}\n", "Changes not staged for commit :".red());
+        for e in unstaged {
+            println!("    {}", e.to_string().red());
+        }
+    }
+    if staged.is_empty() && unstaged.is_empty() {
+        println!("\n  {}", "Nothing to commit, working tree clean".green());
+    }
+    println!();
+}
+
+///
+/// Stage the change `e` in `index`, a deleted path is removed from the index.
+///
+/// # Errors
+///
+/// If the path cannot be added to or removed from the index.
+///
+pub fn stage(index: &mut Index, e: &Entry) -> Result<(), git2::Error> {
+    if let Some(old) = e.old_path.as_deref() {
+        index.remove_path(Path::new(old))?;
+    }
+    match e.kind {
+        Kind::Deleted => index.remove_path(Path::new(e.path.as_str())),
+        _ => index.add_path(Path::new(e.path.as_str())),
+    }
+}
+
+///
+/// Reset the staged changes `entries` to their state in HEAD, or remove them from the index before the first commit.
+///
+/// # Errors
+///
+/// If the index cannot be reset.
+///
+pub fn unstage(repo: &Repository, entries: &[Entry]) -> Result<(), git2::Error> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let paths: Vec<String> = entries.iter().flat_map(Entry::paths).collect();
+    let head: Option<Object<'_>> = repo
+        .head()
+        .ok()
+        .and_then(|h| h.peel(git2::ObjectType::Commit).ok());
+    repo.reset_default(head.as_ref(), paths.iter())
+}
+
+///
+/// A line of a hunk, `+` when added, `-` when removed and ` ` when unchanged. The last line of a file without a final
+/// newline has no newline.
+///
+struct Line {
+    origin: char,
+    content: Vec<u8>,
+}
+
+impl Line {
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.content)
+            .trim_end()
+            .to_string()
+    }
+
+    fn ends_file(&self) -> bool {
+        self.content.ends_with(b"\n").eq(&false)
+    }
+}
+
+///
+/// A hunk of the changes between the index and the working tree of a file.
+///
+struct Hunk {
+    header: String,
+    old_start: usize,
+    old_lines: usize,
+    lines: Vec<Line>,
+}
+
+impl Hunk {
+    fn print(&self) {
+        println!("  {}", self.header.trim_end().cyan());
+        for line in &self.lines {
+            match line.origin {
+                '-' => println!("{} {}", "-".red(), line.text().red()),
+                '+' => println!("{} {}", "+".green(), line.text().green()),
+                _ => println!("  {}", line.text()),
+            }
+            if line.ends_file() {
+                println!("{}", NO_NEWLINE.dimmed());
+            }
+        }
+    }
+}
+
+fn hunks(patch: &Patch<'_>) -> Result<Vec<Hunk>, git2::Error> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for h in 0..patch.num_hunks() {
+        let (hunk, _) = patch.hunk(h)?;
+        let mut lines: Vec<Line> = Vec::new();
+        for l in 0..patch.num_lines_in_hunk(h)? {
+            let line: DiffLine<'_> = patch.line_in_hunk(h, l)?;
+            if matches!(line.origin(), ' ' | '+' | '-') {
+                lines.push(Line {
+                    origin: line.origin(),
+                    content: line.content().to_vec(),
+                });
+            }
+        }
+        hunks.push(Hunk {
+            header: String::from_utf8_lossy(hunk.header()).to_string(),
+            old_start: hunk.old_start() as usize,
+            old_lines: hunk.old_lines() as usize,
+            lines,
+        });
+    }
+    Ok(hunks)
+}
+
+///
+/// Ask which changed lines of `hunk` to stage, every line is selected by default.
+///
+fn pick_lines(hunk: &Hunk) -> Vec<bool> {
+    let picked: Vec<String> = MultiSelect::new("Select the lines to stage :", changed_lines(hunk))
+        .with_all_selected_by_default()
+        .with_page_size(15)
+        .prompt()
+        .unwrap_or_else(crate::cancelled);
+    selected_lines(hunk, picked)
+}
+
+///
+/// The changed lines of `hunk` as they are displayed to pick them.
+///
+fn changed_lines(hunk: &Hunk) -> Vec<String> {
+    hunk.lines
+        .iter()
+        .filter(|l| l.origin.ne(&' '))
+        .map(|l| format!("{} {}", l.origin, l.text()))
+        .collect()
+}
+
+///
+/// The lines of `hunk` selected by the `picked` changed lines, two identical lines being picked one at a time.
+///
+fn selected_lines(hunk: &Hunk, picked: Vec<String>) -> Vec<bool> {
+    let changed: Vec<(usize, String)> = hunk
+        .lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| l.origin.ne(&' '))
+        .map(|(i, l)| (i, format!("{} {}", l.origin, l.text())))
+        .collect();
+    let mut selected: Vec<bool> = vec![false; hunk.lines.len()];
+    let mut remaining: Vec<String> = picked;
+    for (i, l) in &changed {
+        if let Some(x) = remaining.iter().position(|p| p.eq(l)) {
+            remaining.remove(x);
+            selected[*i] = true;
+        }
+    }
+    selected
+}
+
+///
+/// Add `line` to `content`, the newline missing at the end of a file being added when a line follows it.
+///
+fn push_line(content: &mut Vec<u8>, line: &[u8]) {
+    if content.is_empty().eq(&false) && content.ends_with(b"\n").eq(&false) {
+        content.push(b'\n');
+    }
+    content.extend_from_slice(line);
+}
+
+///
+/// Build the content of the index from `old` and the lines of `hunks` selected in `selection`.
+///
+/// The file ends without a newline only when its last kept line has none, e.g. an unselected removal of the last line
+/// of a file without a final newline followed by selected added lines gets back its newline.
+///
+fn apply(old: &[u8], hunks: &[Hunk], selection: &[Vec<bool>]) -> Vec<u8> {
+    let old_lines: Vec<&[u8]> = old.split_inclusive(|b| b.eq(&b'\n')).collect();
+    let mut content: Vec<u8> = Vec::with_capacity(old.len());
+    let mut cursor: usize = 0;
+    for (hunk, selected) in hunks.iter().zip(selection) {
+        let begin: usize = if hunk.old_lines.eq(&0) {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        for line in old_lines.iter().take(begin).skip(cursor) {
+            push_line(&mut content, line);
+        }
+        for (line, keep) in hunk.lines.iter().zip(selected) {
+            match (line.origin, keep) {
+                (' ', _) | ('-', false) | ('+', true) => push_line(&mut content, &line.content),
+                _ => {}
+            }
+        }
+        cursor = begin + hunk.old_lines;
+    }
+    for line in old_lines.iter().skip(cursor) {
+        push_line(&mut content, line);
+    }
+    content
+}
+
+///
+/// Ask which hunks, or lines of a hunk, of the changes made to `file` are staged in `index`.
+///
+/// Return `false` when the file has no hunks, e.g. a binary file, and should be staged as a whole.
+///
+/// # Errors
+///
+/// If the changes of the file cannot be read or staged.
+///
+pub fn stage_hunks(repo: &Repository, index: &mut Index, file: &str) -> Result<bool, git2::Error> {
+    let Some(entry) = index.get_path(Path::new(file), 0) else {
+        return Ok(false);
+    };
+    let (old, hunks): (Vec<u8>, Vec<Hunk>) = {
+        let mut opts: DiffOptions = DiffOptions::new();
+        opts.pathspec(file).disable_pathspec_match(true);
+        let diff: Diff<'_> = repo.diff_index_to_workdir(Some(index), Some(&mut opts))?;
+        let Some(patch) = Patch::from_diff(&diff, 0)? else {
+            return Ok(false);
+        };
+        (repo.find_blob(entry.id)?.content().to_vec(), hunks(&patch)?)
+    };
+    if hunks.is_empty() {
+        return Ok(false);
+    }
+    println!("\n  {}", file.bold());
+    let mut selection: Vec<Vec<bool>> = Vec::new();
+    let mut rest: Option<bool> = None;
+    for (i, hunk) in hunks.iter().enumerate() {
+        let stage: bool = if let Some(all) = rest {
+            all
+        } else {
+            println!();
+            hunk.print();
+            let answer: &str = Select::new(
+                format!("Hunk {}/{} :", i + 1, hunks.len()).as_str(),
+                vec![STAGE, SKIP, SPLIT, STAGE_REST, SKIP_REST],
+            )
+            .prompt()
+            .unwrap_or_else(crate::cancelled);
+            match answer {
+                SPLIT => {
+                    selection.push(pick_lines(hunk));
+                    continue;
+                }
+                STAGE_REST => {
+                    rest = Some(true);
+                    true
+                }
+                SKIP_REST => {
+                    rest = Some(false);
+                    false
+                }
+                _ => answer.eq(STAGE),
+            }
+        };
+        selection.push(vec![stage; hunk.lines.len()]);
+    }
+    let content: Vec<u8> = apply(&old, &hunks, &selection);
+    let entry: IndexEntry = IndexEntry {
+        file_size: u32::try_from(content.len()).unwrap_or(u32::MAX),
+        ..entry
+    };
+    index.add_frombuffer(&entry, &content)?;
+    Ok(true)
+}
+
+///
+/// Display the changes staged in `index` and ask to keep them.
+///
+/// # Errors
+///
+/// If the changes between HEAD and the index cannot be computed.
+///
+pub fn preview(repo: &Repository, index: &Index) -> Result<bool, git2::Error> {
+    let head: Option<Tree<'_>> = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let staged: Diff<'_> = repo.diff_tree_to_index(head.as_ref(), Some(index), None)?;
+    println!("\n  {}\n", "Changes to be committed :".cyan());
+    crate::print_diff(&staged)?;
+    Ok(Confirm::new("Commit these changes ?")
+        .with_default(true)
+        .prompt()
+        .unwrap_or_else(crate::cancelled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunks_of(old: &str, new: &str) -> Vec<Hunk> {
+        let mut opts: DiffOptions = DiffOptions::new();
+        opts.context_lines(1);
+        let patch: Patch<'_> =
+            Patch::from_buffers(old.as_bytes(), None, new.as_bytes(), None, Some(&mut opts))
+                .expect("patch");
+        hunks(&patch).expect("hunks")
+    }
+
+    ///
+    /// The content staged from `old` when the changed lines `picked` of each hunk are selected.
+    ///
+    fn staged(old: &str, new: &str, picked: &[&[&str]]) -> String {
+        let hunks: Vec<Hunk> = hunks_of(old, new);
+        let selection: Vec<Vec<bool>> = hunks
+            .iter()
+            .zip(picked)
+            .map(|(h, p)| selected_lines(h, p.iter().map(|l| (*l).to_string()).collect()))
+            .collect();
+        String::from_utf8(apply(old.as_bytes(), &hunks, &selection)).expect("utf-8")
+    }
+
+    #[test]
+    fn whole_hunks_are_staged_or_skipped() {
+        let old: &str = "a\nb\nc\nd\ne\nf\ng\n";
+        let new: &str = "a\nB\nc\nd\ne\nF\ng\n";
+        assert_eq!(hunks_of(old, new).len(), 2);
+        assert_eq!(
+            staged(old, new, &[&["- b", "+ B"], &[]]),
+            "a\nB\nc\nd\ne\nf\ng\n"
+        );
+        assert_eq!(
+            staged(old, new, &[&[], &["- f", "+ F"]]),
+            "a\nb\nc\nd\ne\nF\ng\n"
+        );
+        assert_eq!(staged(old, new, &[&["- b", "+ B"], &["- f", "+ F"]]), new);
+        assert_eq!(staged(old, new, &[&[], &[]]), old);
+    }
+
+    #[test]
+    fn the_lines_of_a_hunk_are_picked() {
+        let old: &str = "a\nb\nc\n";
+        let new: &str = "a\nx\ny\nc\nz\n";
+        let hunks: Vec<Hunk> = hunks_of(old, new);
+        assert_eq!(changed_lines(&hunks[0]), vec!["- b", "+ x", "+ y", "+ z"]);
+        assert_eq!(staged(old, new, &[&["+ y"]]), "a\nb\ny\nc\n");
+        assert_eq!(staged(old, new, &[&["- b", "+ z"]]), "a\nc\nz\n");
+        assert_eq!(staged(old, new, &[&["- b"]]), "a\nc\n");
+    }
+
+    #[test]
+    fn identical_lines_are_picked_one_at_a_time() {
+        let old: &str = "a\n";
+        let new: &str = "a\nx\nx\n";
+        let hunks: Vec<Hunk> = hunks_of(old, new);
+        assert_eq!(
+            selected_lines(&hunks[0], vec![String::from("+ x")]),
+            vec![false, true, false]
+        );
+        assert_eq!(staged(old, new, &[&["+ x"]]), "a\nx\n");
+    }
+
+    #[test]
+    fn lines_are_added_to_a_file_without_a_final_newline() {
+        let old: &str = "a\nb";
+        let new: &str = "a\nb\nc\n";
+        let hunks: Vec<Hunk> = hunks_of(old, new);
+        assert!(hunks[0].lines.iter().any(Line::ends_file));
+        assert_eq!(staged(old, new, &[&["+ c"]]), "a\nb\nc\n");
+        assert_eq!(staged(old, new, &[&["- b", "+ b", "+ c"]]), new);
+        assert_eq!(staged(old, new, &[&[]]), old);
+    }
+
+    #[test]
+    fn the_final_newline_is_removed() {
+        let old: &str = "a\nb\n";
+        let new: &str = "a\nb";
+        assert_eq!(staged(old, new, &[&["- b", "+ b"]]), new);
+        assert_eq!(staged(old, new, &[&[]]), old);
+        let old: &str = "a\nb\nc\n";
+        let new: &str = "a\nc";
+        assert_eq!(staged(old, new, &[&["- b"]]), "a\nc\n");
+        assert_eq!(staged(old, new, &[&["- b", "- c", "+ c"]]), new);
+    }
+}
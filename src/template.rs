@@ -0,0 +1,644 @@
+use std::collections::BTreeMap;
+use std::env::var;
+use std::fs::read_to_string;
+use std::path::{Path, MAIN_SEPARATOR_STR};
+
+///
+/// A value of a template context.
+///
+#[derive(Clone, Debug)]
+pub enum Value {
+    Text(String),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Self::Text(x) => x.is_empty().eq(&false),
+            Self::List(x) => x.is_empty().eq(&false),
+            Self::Map(x) => x.is_empty().eq(&false),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Map(x) => x.get(key),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(x: &str) -> Self {
+        Self::Text(x.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(x: String) -> Self {
+        Self::Text(x)
+    }
+}
+
+impl From<&[String]> for Value {
+    fn from(x: &[String]) -> Self {
+        Self::List(x.iter().map(|v| Self::from(v.as_str())).collect())
+    }
+}
+
+impl<const N: usize> From<[(&str, Self); N]> for Value {
+    fn from(x: [(&str, Self); N]) -> Self {
+        Self::Map(x.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Var(String, usize),
+    If {
+        path: String,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+        line: usize,
+    },
+    Each {
+        path: String,
+        body: Vec<Node>,
+        line: usize,
+    },
+}
+
+enum Token {
+    Text(String),
+    Tag(String, usize),
+}
+
+impl Token {
+    fn is_block(&self) -> bool {
+        match self {
+            Self::Tag(t, _) => {
+                t.starts_with('#') || t.starts_with('/') || t.starts_with('!') || t.eq("else")
+            }
+            Self::Text(_) => false,
+        }
+    }
+}
+
+///
+/// A parsed template.
+///
+/// `{{name}}` is replaced by a value, `{{#if name}}…{{else}}…{{/if}}` and `{{#unless name}}…{{/unless}}` keep a
+/// section when the value is not empty, `{{#each name}}…{{/each}}` repeat a section for each item of a list, the item
+/// being `{{this}}` and its position `{{@index}}`, `{{! comment }}` is dropped. Fields are reached with dots, e.g.
+/// `{{project.name}}`, and fields of the current item are directly available inside `each`. A line containing only a
+/// section tag is removed from the output.
+///
+#[derive(Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    ///
+    /// Parse a template.
+    ///
+    /// # Errors
+    ///
+    /// The list of syntax errors.
+    ///
+    pub fn parse(source: &str) -> Result<Self, Vec<String>> {
+        let mut tokens: Vec<Token> = tokenize(source)?;
+        strip_standalone(&mut tokens);
+        let mut errors: Vec<String> = Vec::new();
+        let mut iter = tokens.into_iter();
+        let (nodes, end) = parse_nodes(&mut iter, &mut errors);
+        if let Some((tag, line)) = end {
+            errors.push(format!("line {line} : unexpected {{{{{tag}}}}}"));
+        }
+        if errors.is_empty() {
+            Ok(Self { nodes })
+        } else {
+            Err(errors)
+        }
+    }
+
+    ///
+    /// Render the template with the `context`.
+    ///
+    /// # Errors
+    ///
+    /// The list of unknown placeholders or invalid sections.
+    ///
+    pub fn render(&self, context: &Value) -> Result<String, Vec<String>> {
+        let mut out: String = String::new();
+        let mut errors: Vec<String> = Vec::new();
+        render_nodes(&self.nodes, &mut vec![context], &mut out, &mut errors);
+        if errors.is_empty() {
+            Ok(out)
+        } else {
+            Err(errors)
+        }
+    }
+
+    ///
+    /// Check every placeholder of every section against a `sample` context, lists of the sample must have an item.
+    ///
+    #[must_use]
+    pub fn check(&self, sample: &Value) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+        check_nodes(&self.nodes, &mut vec![sample], &mut errors);
+        errors
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, Vec<String>> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut rest: &str = source;
+    let mut line: usize = 1;
+    while let Some(start) = rest.find("{{") {
+        let (text, tail) = rest.split_at(start);
+        line += text.matches('\n').count();
+        if text.is_empty().eq(&false) {
+            tokens.push(Token::Text(text.to_string()));
+        }
+        let Some(end) = tail.find("}}") else {
+            return Err(vec![format!("line {line} : unclosed {{{{")]);
+        };
+        let tag: &str = &tail[2..end];
+        tokens.push(Token::Tag(tag.trim().to_string(), line));
+        line += tag.matches('\n').count();
+        rest = &tail[end + 2..];
+    }
+    if rest.is_empty().eq(&false) {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+    Ok(tokens)
+}
+
+fn starts_line(tokens: &[Token], i: usize) -> bool {
+    if i.eq(&0) {
+        return true;
+    }
+    match &tokens[i - 1] {
+        Token::Text(t) => {
+            let tail: &str = t.rsplit('\n').next().unwrap_or_default();
+            tail.trim_matches([' ', '\t']).is_empty() && (t.contains('\n') || i.eq(&1))
+        }
+        Token::Tag(..) => false,
+    }
+}
+
+fn ends_line(tokens: &[Token], i: usize) -> bool {
+    match tokens.get(i + 1) {
+        None => true,
+        Some(Token::Text(t)) => {
+            let head: &str = t.split('\n').next().unwrap_or_default();
+            head.trim_matches([' ', '\t', '\r']).is_empty()
+        }
+        Some(Token::Tag(..)) => false,
+    }
+}
+
+fn strip_standalone(tokens: &mut [Token]) {
+    let standalone: Vec<usize> = (0..tokens.len())
+        .filter(|i| tokens[*i].is_block() && starts_line(tokens, *i) && ends_line(tokens, *i))
+        .collect();
+    for i in standalone {
+        if i > 0 {
+            if let Token::Text(t) = &mut tokens[i - 1] {
+                let keep: usize = t.rfind('\n').map_or(0, |x| x + 1);
+                t.truncate(keep);
+            }
+        }
+        if let Some(Token::Text(t)) = tokens.get_mut(i + 1) {
+            *t = t
+                .find('\n')
+                .map_or_else(String::new, |x| t[x + 1..].to_string());
+        }
+    }
+}
+
+fn parse_nodes(
+    tokens: &mut impl Iterator<Item = Token>,
+    errors: &mut Vec<String>,
+) -> (Vec<Node>, Option<(String, usize)>) {
+    let mut nodes: Vec<Node> = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(t) => {
+                if t.is_empty().eq(&false) {
+                    nodes.push(Node::Text(t));
+                }
+            }
+            Token::Tag(t, line) => {
+                if t.starts_with('!') {
+                    continue;
+                }
+                if t.starts_with('/') || t.eq("else") {
+                    return (nodes, Some((t, line)));
+                }
+                if let Some(rest) = t.strip_prefix('#') {
+                    let (kind, path) = rest.split_once(' ').unwrap_or((rest, ""));
+                    let path: String = path.trim().to_string();
+                    if path.is_empty() {
+                        errors.push(format!("line {line} : missing name in {{{{{t}}}}}"));
+                    }
+                    match kind {
+                        "if" | "unless" => {
+                            let (then, end) = parse_nodes(tokens, errors);
+                            let (otherwise, end) = match end {
+                                Some((e, _)) if e.eq("else") => parse_nodes(tokens, errors),
+                                _ => (Vec::new(), end),
+                            };
+                            close(kind, end, line, errors);
+                            nodes.push(Node::If {
+                                path,
+                                negate: kind.eq("unless"),
+                                then,
+                                otherwise,
+                                line,
+                            });
+                        }
+                        "each" => {
+                            let (body, end) = parse_nodes(tokens, errors);
+                            close(kind, end, line, errors);
+                            nodes.push(Node::Each { path, body, line });
+                        }
+                        _ => errors.push(format!("line {line} : unknown section {{{{#{kind}}}}}")),
+                    }
+                    continue;
+                }
+                nodes.push(Node::Var(t, line));
+            }
+        }
+    }
+    (nodes, None)
+}
+
+fn close(kind: &str, end: Option<(String, usize)>, line: usize, errors: &mut Vec<String>) {
+    match end {
+        Some((e, _)) if e.trim_start_matches('/').eq(kind) => {}
+        Some((e, l)) => errors.push(format!(
+            "line {l} : expected {{{{/{kind}}}}} opened line {line}, found {{{{{e}}}}}"
+        )),
+        None => errors.push(format!("line {line} : {{{{#{kind}}}}} is never closed")),
+    }
+}
+
+fn lookup<'a>(scopes: &[&'a Value], path: &str) -> Option<&'a Value> {
+    let mut parts = path.split('.');
+    let first: &str = parts.next().unwrap_or_default();
+    let mut value: &Value = if first.eq("this") {
+        scopes.last().copied()?
+    } else {
+        scopes.iter().rev().find_map(|s| s.get(first))?
+    };
+    for part in parts {
+        value = value.get(part)?;
+    }
+    Some(value)
+}
+
+fn render_nodes<'a>(
+    nodes: &'a [Node],
+    scopes: &mut Vec<&'a Value>,
+    out: &mut String,
+    errors: &mut Vec<String>,
+) {
+    for node in nodes {
+        match node {
+            Node::Text(t) => out.push_str(t),
+            Node::Var(path, line) => match lookup(scopes, path) {
+                Some(Value::Text(t)) => out.push_str(t),
+                Some(_) => errors.push(format!("line {line} : {path} is not a text")),
+                None => errors.push(format!("line {line} : unknown placeholder {path}")),
+            },
+            Node::If {
+                path,
+                negate,
+                then,
+                otherwise,
+                line,
+            } => match lookup(scopes, path) {
+                Some(v) => {
+                    let branch = if v.truthy().ne(negate) {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    render_nodes(branch, scopes, out, errors);
+                }
+                None => errors.push(format!("line {line} : unknown placeholder {path}")),
+            },
+            Node::Each { path, body, line } => match lookup(scopes, path) {
+                Some(Value::List(items)) => {
+                    for (i, item) in items.iter().enumerate() {
+                        let index: Value = Value::Text(i.to_string());
+                        let mut inner: Vec<&Value> = scopes.clone();
+                        let position: Value = Value::from([("@index", index)]);
+                        inner.push(&position);
+                        inner.push(item);
+                        render_nodes(body, &mut inner, out, errors);
+                    }
+                }
+                Some(_) => errors.push(format!("line {line} : {path} is not a list")),
+                None => errors.push(format!("line {line} : unknown placeholder {path}")),
+            },
+        }
+    }
+}
+
+fn check_nodes<'a>(nodes: &'a [Node], scopes: &mut Vec<&'a Value>, errors: &mut Vec<String>) {
+    let index: Value = Value::from([("@index", Value::from("0"))]);
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Var(path, line) => {
+                if lookup(scopes, path).is_none() {
+                    errors.push(format!("line {line} : unknown placeholder {path}"));
+                }
+            }
+            Node::If {
+                path,
+                then,
+                otherwise,
+                line,
+                ..
+            } => {
+                if lookup(scopes, path).is_none() {
+                    errors.push(format!("line {line} : unknown placeholder {path}"));
+                }
+                check_nodes(then, scopes, errors);
+                check_nodes(otherwise, scopes, errors);
+            }
+            Node::Each { path, body, line } => match lookup(scopes, path) {
+                Some(Value::List(items)) => {
+                    let mut inner: Vec<&Value> = scopes.clone();
+                    inner.push(&index);
+                    if let Some(item) = items.first() {
+                        inner.push(item);
+                    }
+                    check_nodes(body, &mut inner, errors);
+                }
+                Some(_) => errors.push(format!("line {line} : {path} is not a list")),
+                None => errors.push(format!("line {line} : unknown placeholder {path}")),
+            },
+        }
+    }
+}
+
+///
+/// Find the template `name` of the repository `r`.
+///
+/// The `zazen` directory of the repository is used first, then the zazen directory of the user configuration.
+///
+#[must_use]
+pub fn find(r: &str, name: &str) -> Option<String> {
+    let project: String = format!("{r}{MAIN_SEPARATOR_STR}zazen{MAIN_SEPARATOR_STR}{name}");
+    if Path::new(project.as_str()).is_file() {
+        return Some(project);
+    }
+    let user: String = format!("{}{MAIN_SEPARATOR_STR}{name}", config_dir()?);
+    if Path::new(user.as_str()).is_file() {
+        return Some(user);
+    }
+    None
+}
+
+///
+/// The zazen directory of the user configuration.
+///
+#[must_use]
+pub fn config_dir() -> Option<String> {
+    var("XDG_CONFIG_HOME")
+        .ok()
+        .filter(|x| x.is_empty().eq(&false))
+        .or_else(|| {
+            var("HOME")
+                .or_else(|_| var("USERPROFILE"))
+                .ok()
+                .map(|h| format!("{h}{MAIN_SEPARATOR_STR}.config"))
+        })
+        .map(|c| format!("{c}{MAIN_SEPARATOR_STR}zazen"))
+}
+
+///
+/// Load and check the template `name` against the `sample` context, `default` is used when there is no file.
+///
+/// # Errors
+///
+/// The path of the template with the reason it cannot be read or its errors.
+///
+pub fn load(r: &str, name: &str, default: &str, sample: &Value) -> Result<Template, String> {
+    let (path, source) = match find(r, name) {
+        Some(p) => {
+            let source: String = read_to_string(p.as_str()).map_err(|e| format!("{p} : {e}"))?;
+            (p, source)
+        }
+        None => (String::from("built-in"), default.to_string()),
+    };
+    let template: Template =
+        Template::parse(source.as_str()).map_err(|e| format!("{path} :\n\t{}", e.join("\n\t")))?;
+    let errors: Vec<String> = template.check(sample);
+    if errors.is_empty() {
+        Ok(template)
+    } else {
+        Err(format!("{path} :\n\t{}", errors.join("\n\t")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load, Template, Value};
+    use crate::message::CommitMessage;
+    use std::fs;
+
+    fn render(source: &str, context: &Value) -> String {
+        Template::parse(source)
+            .expect("valid template")
+            .render(context)
+            .expect("rendered")
+    }
+
+    fn errors(source: &str) -> Vec<String> {
+        Template::parse(source).expect_err("invalid template")
+    }
+
+    fn context() -> Value {
+        Value::from([
+            ("name", Value::from("zazen")),
+            ("empty", Value::from("")),
+            (
+                "items",
+                Value::from(["a".to_string(), "b".to_string()].as_slice()),
+            ),
+            ("none", Value::List(Vec::new())),
+            ("project", Value::from([("version", Value::from("1.0.0"))])),
+            (
+                "people",
+                Value::List(vec![
+                    Value::from([("who", Value::from("Ann"))]),
+                    Value::from([("who", Value::from("Bob"))]),
+                ]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn parse_reports_unclosed_tags() {
+        assert_eq!(errors("a {{name"), vec!["line 1 : unclosed {{"]);
+        assert_eq!(
+            errors("{{#if name}}\nx\n"),
+            vec!["line 1 : {{#if}} is never closed"]
+        );
+        assert_eq!(
+            errors("{{#each items}}"),
+            vec!["line 1 : {{#each}} is never closed"]
+        );
+    }
+
+    #[test]
+    fn parse_reports_mismatched_blocks() {
+        assert_eq!(
+            errors("{{#if name}}\nx\n{{/each}}"),
+            vec!["line 3 : expected {{/if}} opened line 1, found {{/each}}"]
+        );
+        assert_eq!(errors("x{{/if}}"), vec!["line 1 : unexpected {{/if}}"]);
+        assert_eq!(errors("x{{else}}"), vec!["line 1 : unexpected {{else}}"]);
+        assert_eq!(
+            errors("{{#with name}}"),
+            vec!["line 1 : unknown section {{#with}}"]
+        );
+        assert_eq!(
+            errors("{{#if}}x{{/if}}"),
+            vec!["line 1 : missing name in {{#if}}"]
+        );
+    }
+
+    #[test]
+    fn standalone_section_lines_are_removed() {
+        assert_eq!(
+            render("a\n{{#if name}}\nb\n{{/if}}\nc\n", &context()),
+            "a\nb\nc\n"
+        );
+        assert_eq!(
+            render(
+                "a\n  {{#each items}}  \n- {{this}}\n{{/each}}\nc",
+                &context()
+            ),
+            "a\n- a\n- b\nc"
+        );
+        assert_eq!(render("{{! a comment }}\nx\n", &context()), "x\n");
+    }
+
+    #[test]
+    fn inline_sections_are_kept_on_their_line() {
+        assert_eq!(render("a {{#if name}}b{{/if}} c\n", &context()), "a b c\n");
+        assert_eq!(render("a {{name}}\n", &context()), "a zazen\n");
+    }
+
+    #[test]
+    fn if_else_and_unless() {
+        let c: Value = context();
+        assert_eq!(render("{{#if name}}yes{{else}}no{{/if}}", &c), "yes");
+        assert_eq!(render("{{#if empty}}yes{{else}}no{{/if}}", &c), "no");
+        assert_eq!(render("{{#if none}}yes{{else}}no{{/if}}", &c), "no");
+        assert_eq!(render("{{#unless empty}}yes{{/unless}}", &c), "yes");
+        assert_eq!(render("{{#unless name}}yes{{else}}no{{/unless}}", &c), "no");
+    }
+
+    #[test]
+    fn each_gives_the_item_its_index_and_its_fields() {
+        let c: Value = context();
+        assert_eq!(
+            render("{{#each items}}{{@index}}={{this}} {{/each}}", &c),
+            "0=a 1=b "
+        );
+        assert_eq!(
+            render("{{#each people}}{{who}}/{{name}} {{/each}}", &c),
+            "Ann/zazen Bob/zazen "
+        );
+        assert_eq!(render("{{#each none}}x{{/each}}", &c), "");
+        assert_eq!(render("{{project.version}}", &c), "1.0.0");
+    }
+
+    #[test]
+    fn render_reports_invalid_values() {
+        let t: Template = Template::parse("{{missing}}\n{{items}}\n{{#each name}}{{/each}}")
+            .expect("valid template");
+        assert_eq!(
+            t.render(&context()).expect_err("invalid values"),
+            vec![
+                "line 1 : unknown placeholder missing",
+                "line 2 : items is not a text",
+                "line 3 : name is not a list",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_visits_every_branch() {
+        let t: Template =
+            Template::parse("{{#if empty}}{{a}}{{else}}{{b}}{{/if}}{{#each none}}{{c}}{{/each}}")
+                .expect("valid template");
+        assert_eq!(
+            t.check(&context()),
+            vec![
+                "line 1 : unknown placeholder a",
+                "line 1 : unknown placeholder b",
+                "line 1 : unknown placeholder c",
+            ]
+        );
+        assert!(
+            Template::parse("{{#each people}}{{who}} {{@index}}{{/each}}")
+                .expect("valid template")
+                .check(&context())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn the_commit_template_renders_every_part() {
+        assert_eq!(
+            render(
+                include_str!("commit-template"),
+                &CommitMessage::sample().context()
+            ),
+            "Star(scope): summary\n\n\tThe following changes were made :\n\n\t\t* why\n\n\tThe following changes break :\n\n\t\t* breaking\n\n\tAuthored by :\n\n\t\t* author <email> the date\n\nFixes: #1\nCloses: #2\nCo-authored-by: author <email>\n"
+        );
+    }
+
+    #[test]
+    fn the_conventional_template_renders_every_part() {
+        let mut m: CommitMessage = CommitMessage::sample();
+        m.commit_type = String::from("feat");
+        assert_eq!(
+            render(include_str!("conventional-template"), &m.context()),
+            "feat(scope)!: summary\n\n- why\n\nBREAKING-CHANGE: breaking\nFixes: #1\nCloses: #2\nCo-authored-by: author <email>\n"
+        );
+        m.breaking.clear();
+        m.why.clear();
+        assert_eq!(
+            render(include_str!("conventional-template"), &m.context()),
+            "feat(scope): summary\n\nFixes: #1\nCloses: #2\nCo-authored-by: author <email>\n"
+        );
+    }
+
+    #[test]
+    fn load_reports_an_unreadable_template() {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let zazen = dir.path().join("zazen");
+        fs::create_dir(&zazen).expect("zazen directory");
+        fs::write(zazen.join("commit-template"), [0xff, 0xfe]).expect("template");
+        let r: &str = dir.path().to_str().expect("utf-8 path");
+        let sample: Value = CommitMessage::sample().context();
+        let error: String =
+            load(r, "commit-template", "{{type}}", &sample).expect_err("unreadable template");
+        assert!(error.contains("commit-template"), "{error}");
+        assert!(load(r, "missing-template", "{{type}}", &sample).is_ok());
+    }
+}
@@ -0,0 +1,157 @@
+use git2::Repository;
+use std::collections::BTreeSet;
+
+/// The trailer of the co-authors of a commit.
+pub const CO_AUTHORED_BY: &str = "Co-authored-by";
+/// The trailer certifying the Developer Certificate of Origin.
+pub const SIGNED_OFF_BY: &str = "Signed-off-by";
+/// The trailer of the issues resolved by a commit.
+pub const FIXES: &str = "Fixes";
+/// The trailer of the issues closed by a commit.
+pub const CLOSES: &str = "Closes";
+/// The trailer of the issues referenced by a commit.
+pub const REFS: &str = "Refs";
+/// The trailer of a breaking change, `BREAKING CHANGE` being accepted when parsing.
+pub const BREAKING_CHANGE: &str = "BREAKING-CHANGE";
+
+const ISSUE_KEYS: [&str; 3] = [FIXES, CLOSES, REFS];
+
+const HISTORY_DEPTH: usize = 1000;
+
+///
+/// Split a `Key: value` trailer line, the key containing only letters, digits and dashes, or a `Fixes #1`,
+/// `Closes #1` or `Refs #1` issue line.
+///
+#[must_use]
+pub fn parse(line: &str) -> Option<(String, String)> {
+    let line: &str = line.trim();
+    let (key, value): (&str, String) = match line.split_once(": ") {
+        Some((k, v)) => (k, v.to_string()),
+        None => line
+            .split_once(" #")
+            .filter(|(k, _)| ISSUE_KEYS.iter().any(|x| x.eq_ignore_ascii_case(k)))
+            .map(|(k, v)| (k, format!("#{v}")))?,
+    };
+    let key: &str = if key.eq("BREAKING CHANGE") {
+        BREAKING_CHANGE
+    } else {
+        key
+    };
+    let valid: bool = key.is_empty().eq(&false)
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c.eq(&'-'))
+        && value.trim().is_empty().eq(&false);
+    valid.then(|| (key.to_string(), value.trim().to_string()))
+}
+
+///
+/// Check if the trailer `key` holds free text, not a person like `Co-authored-by` nor an issue like `Refs`.
+///
+#[must_use]
+pub fn is_prose(key: &str) -> bool {
+    key.to_ascii_lowercase().ends_with("-by").eq(&false)
+        && ISSUE_KEYS
+            .iter()
+            .any(|x| x.eq_ignore_ascii_case(key))
+            .eq(&false)
+}
+
+///
+/// Check if every line of `paragraph` is a trailer.
+///
+#[must_use]
+pub fn is_block(paragraph: &[&str]) -> bool {
+    paragraph.is_empty().eq(&false) && paragraph.iter().all(|l| parse(l).is_some())
+}
+
+///
+/// The `Name <email>` of the authors and committers of the history of `r`, the most recent first.
+///
+#[must_use]
+pub fn authors(r: &str) -> Vec<String> {
+    let Ok(repo) = Repository::open(r) else {
+        return Vec::new();
+    };
+    let Ok(mut revwalk) = repo.revwalk() else {
+        return Vec::new();
+    };
+    if revwalk.push_head().is_err() {
+        return Vec::new();
+    }
+    let me: Option<String> = repo.signature().ok().map(|s| s.to_string());
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    let mut authors: Vec<String> = Vec::new();
+    for commit in revwalk
+        .take(HISTORY_DEPTH)
+        .flatten()
+        .filter_map(|o| repo.find_commit(o).ok())
+    {
+        let mut people: Vec<String> =
+            vec![commit.author().to_string(), commit.committer().to_string()];
+        people.extend(
+            commit
+                .message()
+                .unwrap_or_default()
+                .lines()
+                .filter_map(parse)
+                .filter(|(k, _)| k.eq_ignore_ascii_case(CO_AUTHORED_BY))
+                .map(|(_, v)| v),
+        );
+        for p in people {
+            if me.as_ref().is_some_and(|m| m.eq(&p)).eq(&false) && seen.insert(p.clone()) {
+                authors.push(p);
+            }
+        }
+    }
+    authors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_block, is_prose, parse};
+
+    fn trailer(k: &str, v: &str) -> Option<(String, String)> {
+        Some((k.to_string(), v.to_string()))
+    }
+
+    #[test]
+    fn key_value_trailers() {
+        assert_eq!(
+            parse("Co-authored-by: Ann <ann@example.com>"),
+            trailer("Co-authored-by", "Ann <ann@example.com>")
+        );
+        assert_eq!(
+            parse("BREAKING CHANGE: drop v1"),
+            trailer("BREAKING-CHANGE", "drop v1")
+        );
+        assert_eq!(parse("not a key: value"), None);
+        assert_eq!(parse("Key: "), None);
+    }
+
+    #[test]
+    fn issue_lines_only_for_the_issue_keys() {
+        assert_eq!(parse("Fixes #1"), trailer("Fixes", "#1"));
+        assert_eq!(parse("closes #2"), trailer("closes", "#2"));
+        assert_eq!(parse("Refs #3"), trailer("Refs", "#3"));
+        assert_eq!(parse("PR #3"), None);
+        assert_eq!(parse("see PR #3"), None);
+    }
+
+    #[test]
+    fn blocks_need_every_line_to_be_a_trailer() {
+        assert!(is_block(&[
+            "Fixes #1",
+            "Signed-off-by: Ann <ann@example.com>"
+        ]));
+        assert!(is_block(&["Fixes #1", "see PR #3"]).eq(&false));
+        assert!(is_block(&[]).eq(&false));
+    }
+
+    #[test]
+    fn only_the_trailers_of_free_text_are_prose() {
+        assert!(is_prose("Note"));
+        assert!(is_prose("BREAKING-CHANGE"));
+        assert!(is_prose("Co-authored-by").eq(&false));
+        assert!(is_prose("reviewed-by").eq(&false));
+        assert!(is_prose("refs").eq(&false));
+    }
+}
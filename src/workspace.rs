@@ -0,0 +1,166 @@
+use cargo_metadata::{Metadata, MetadataCommand, Package};
+use git2::Repository;
+use inquire::Select;
+use std::env::current_dir;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static SELECTED: OnceLock<String> = OnceLock::new();
+
+///
+/// A package of the workspace other than the package at the root of the repository, with its directory relative to
+/// the root of the repository.
+///
+#[derive(Clone, Debug)]
+pub struct Member {
+    pub name: String,
+    pub directory: PathBuf,
+}
+
+///
+/// Use the package `name` of the workspace instead of the root package.
+///
+/// # Panics
+///
+/// If a package has already been selected.
+///
+pub fn select(name: &str) {
+    assert!(
+        SELECTED.set(name.to_string()).is_ok(),
+        "a package is already selected"
+    );
+}
+
+///
+/// The name of the package to use : the selected one, otherwise the root package of the workspace, otherwise the
+/// only member, otherwise the member chosen at a prompt, the choice being kept for the next calls.
+///
+/// # Errors
+///
+/// If the prompt fails or is cancelled.
+///
+fn selected(metadata: &Metadata) -> Result<String, String> {
+    if let Some(name) = SELECTED.get() {
+        return Ok(name.clone());
+    }
+    let name: String = if let Some(root) = metadata.root_package() {
+        root.name.clone()
+    } else {
+        let mut members: Vec<String> = metadata
+            .workspace_packages()
+            .iter()
+            .map(|p| p.name.clone())
+            .collect();
+        if members.len().eq(&1) {
+            members.remove(0)
+        } else {
+            members.sort();
+            Select::new("Select the package of the workspace : ", members)
+                .with_help_message("use --package to skip this prompt")
+                .prompt()
+                .map_err(|e| e.to_string())?
+        }
+    };
+    Ok(SELECTED.get_or_init(|| name).clone())
+}
+
+///
+/// The workspace of the current directory, `None` when no `Cargo.toml` is found in it or in its parents.
+///
+/// # Errors
+///
+/// If cargo cannot read the workspace.
+///
+fn metadata() -> Result<Option<Metadata>, String> {
+    let Ok(directory) = current_dir() else {
+        return Ok(None);
+    };
+    if directory
+        .ancestors()
+        .any(|d| d.join("Cargo.toml").is_file())
+        .eq(&false)
+    {
+        return Ok(None);
+    }
+    MetadataCommand::new()
+        .no_deps()
+        .exec()
+        .map(Some)
+        .map_err(|e| format!("failed to read the cargo workspace : {e}"))
+}
+
+///
+/// The selected package of the workspace `metadata`.
+///
+/// # Errors
+///
+/// If the package prompt fails or the selected package is not one of the members.
+///
+fn find(metadata: &Metadata) -> Result<Package, String> {
+    let name: String = selected(metadata)?;
+    let packages: Vec<&Package> = metadata.workspace_packages();
+    if let Some(package) = packages.iter().find(|p| p.name.eq(&name)) {
+        return Ok((*package).clone());
+    }
+    let mut names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+    names.sort_unstable();
+    Err(format!(
+        "unknown package {name}, expected one of {}",
+        names.join(", ")
+    ))
+}
+
+///
+/// The package of the workspace of the current directory zazen works on.
+///
+/// # Errors
+///
+/// If the current directory is not a cargo project, its workspace cannot be read or the selected package is not one
+/// of its members.
+///
+pub fn package() -> Result<Package, String> {
+    find(&metadata()?.ok_or_else(|| String::from("no cargo project"))?)
+}
+
+///
+/// The package zazen works on when it is not at the root of `repo`, `None` when it is at the root or when `repo` is
+/// not a cargo project, the changelogs and the readme then covering the whole repository.
+///
+/// # Errors
+///
+/// As for [`package`], if the workspace cannot be read or the selected package is not one of its members.
+///
+pub fn member(repo: &Repository) -> Result<Option<Member>, String> {
+    let Some(metadata) = metadata()? else {
+        return Ok(None);
+    };
+    let package: Package = find(&metadata)?;
+    Ok(relative(
+        repo,
+        package.manifest_path.parent().map(|d| d.as_std_path()),
+    )
+    .map(|directory| Member {
+        name: package.name,
+        directory,
+    }))
+}
+
+///
+/// The path of the package `directory` relative to the root of `repo`, `None` for the root itself or a directory
+/// outside of it.
+///
+fn relative(repo: &Repository, directory: Option<&Path>) -> Option<PathBuf> {
+    let directory: &Path = directory?;
+    let root: &Path = repo.workdir()?;
+    let relative: PathBuf = directory
+        .canonicalize()
+        .unwrap_or_else(|_| directory.to_path_buf())
+        .strip_prefix(root.canonicalize().unwrap_or_else(|_| root.to_path_buf()))
+        .ok()?
+        .to_path_buf();
+    relative
+        .as_os_str()
+        .is_empty()
+        .eq(&false)
+        .then_some(relative)
+}